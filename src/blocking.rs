@@ -0,0 +1,104 @@
+//! Blocking NWS API
+//!
+//! Equivalents of the async getters using reqwest::blocking::Client,
+//! for callers without an async runtime.
+use crate::client::{decode_body, rate_limited, DEFAULT_BASE_URL, DEFAULT_USER_AGENT};
+use crate::{print_report, report_bundles, Error, Forecast, Point, PointInfo};
+use log::debug;
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
+
+/// GET /points/{lat},{lng}
+pub fn get_point(pnt: &Point, client: &Client) -> Result<PointInfo, Error> {
+  get_point_from(DEFAULT_BASE_URL, pnt, client)
+}
+
+fn get_point_from(base: &str, pnt: &Point, client: &Client) -> Result<PointInfo, Error> {
+  get_json(
+    &format!("{}points/{}", base, pnt.nws_coordinates()?),
+    client,
+  )
+}
+
+/// GET the forecast of a point
+pub fn get_forecast(pnt: &PointInfo, client: &Client) -> Result<Forecast, Error> {
+  get_json(&pnt.properties.forecast, client)
+}
+
+/// GET the hourly forecast of a point
+pub fn get_forecast_hourly(pnt: &PointInfo, client: &Client) -> Result<Forecast, Error> {
+  get_json(&pnt.properties.forecast_hourly, client)
+}
+
+/// Print the first 10 periods of the hourly forecast at a coordinate,
+/// using a Client with the DEFAULT_USER_AGENT
+pub fn weather_report(lat: f32, lng: f32) -> Result<(), Error> {
+  let client = Client::builder().user_agent(DEFAULT_USER_AGENT).build()?;
+  weather_report_from(DEFAULT_BASE_URL, lat, lng, &client)
+}
+
+fn weather_report_from(base: &str, lat: f32, lng: f32, client: &Client) -> Result<(), Error> {
+  let point = Point { lat, lng };
+
+  let res = get_point_from(base, &point, client)?;
+  let resf = get_forecast_hourly(&res, client)?;
+  print_report(&report_bundles(&resf));
+  Ok(())
+}
+
+fn get_json<T: DeserializeOwned>(url: &str, client: &Client) -> Result<T, Error> {
+  debug!("GET {}", url);
+  let response = client.get(url).send()?;
+  let status = response.status();
+  if let Some(e) = rate_limited(status, response.headers()) {
+    return Err(e);
+  }
+  decode_body(status, response.text()?)
+}
+
+#[test]
+fn blocking_get_point() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let rt = tokio::runtime::Runtime::new().unwrap();
+  let server = rt.block_on(async {
+    let server = MockServer::start().await;
+    Mock::given(path("/points/40.7128,-74.006"))
+      .respond_with(ResponseTemplate::new(200).set_body_string(crate::TEST_POINT))
+      .mount(&server)
+      .await;
+    server
+  });
+  let base = format!("{}/", server.uri());
+  let res = get_point_from(&base, &Point::new(40.7128, -74.006), &Client::new()).unwrap();
+  assert_eq!(res.properties.radar_station, "KOKX");
+}
+
+#[test]
+fn blocking_weather_report() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let rt = tokio::runtime::Runtime::new().unwrap();
+  let server = rt.block_on(async {
+    let server = MockServer::start().await;
+    Mock::given(path("/points/40.7128,-74.006"))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .set_body_string(crate::TEST_POINT.replace("https://api.weather.gov", &server.uri())),
+      )
+      .expect(1)
+      .mount(&server)
+      .await;
+    Mock::given(path("/gridpoints/OKX/33,35/forecast/hourly"))
+      .respond_with(ResponseTemplate::new(200).set_body_string(crate::test_forecast_json(2)))
+      .expect(1)
+      .mount(&server)
+      .await;
+    server
+  });
+  let base = format!("{}/", server.uri());
+  weather_report_from(&base, 40.7128, -74.006, &Client::new()).unwrap();
+  rt.block_on(server.verify());
+}
@@ -0,0 +1,148 @@
+//! In-memory TTL cache
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Bounded map whose entries expire `ttl` after insertion
+#[derive(Debug)]
+pub struct TtlCache<K, V> {
+  capacity: usize,
+  ttl: Duration,
+  /// Values and the Instant they expire
+  entries: HashMap<K, (Instant, V)>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+  /// Create a new TtlCache holding at most `capacity` entries
+  pub fn new(capacity: usize, ttl: Duration) -> Self {
+    TtlCache {
+      capacity,
+      ttl,
+      entries: HashMap::new(),
+    }
+  }
+
+  /// Get a clone of the value at `key` if it has not expired
+  pub fn get(&self, key: &K) -> Option<V> {
+    match self.entries.get(key) {
+      Some((expires, value)) if Instant::now() < *expires => Some(value.clone()),
+      _ => None,
+    }
+  }
+
+  /// Insert a value, evicting expired entries and then the oldest
+  /// entry if the cache is full
+  pub fn insert(&mut self, key: K, value: V) {
+    self.insert_with_ttl(key, value, self.ttl)
+  }
+
+  /// Same as insert, but the value expires `ttl` after insertion
+  /// instead of the cache's ttl
+  pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) {
+    if self.capacity == 0 {
+      return;
+    }
+    let now = Instant::now();
+    if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+      self.entries.retain(|_, (expires, _)| now < *expires);
+      if self.entries.len() >= self.capacity {
+        let soonest = self
+          .entries
+          .iter()
+          .min_by_key(|(_, (expires, _))| *expires)
+          .map(|(k, _)| k.clone());
+        if let Some(soonest) = soonest {
+          self.entries.remove(&soonest);
+        }
+      }
+    }
+    self.entries.insert(key, (now + ttl, value));
+  }
+}
+
+/// How long a response stays fresh, from its Cache-Control and Expires
+/// headers. Cache-Control max-age takes precedence, and no-store or
+/// no-cache means it should not be cached. Returns None if the
+/// response is not cacheable or already stale.
+pub fn freshness(
+  cache_control: Option<&str>,
+  expires: Option<&str>,
+  now: DateTime<Utc>,
+) -> Option<Duration> {
+  if let Some(cache_control) = cache_control {
+    let mut max_age = None;
+    for directive in cache_control.split(',').map(str::trim) {
+      if directive.eq_ignore_ascii_case("no-store") || directive.eq_ignore_ascii_case("no-cache") {
+        return None;
+      }
+      if let Some(secs) = directive.strip_prefix("max-age=") {
+        max_age = secs.parse().ok().map(Duration::from_secs);
+      }
+    }
+    if max_age.is_some() {
+      return max_age.filter(|d| !d.is_zero());
+    }
+  }
+  let expires = DateTime::parse_from_rfc2822(expires?.trim()).ok()?;
+  (expires.with_timezone(&Utc) - now)
+    .to_std()
+    .ok()
+    .filter(|d| !d.is_zero())
+}
+
+#[test]
+fn ttl_cache_expiry() {
+  let mut cache = TtlCache::new(2, Duration::ZERO);
+  cache.insert("a", 1);
+  assert_eq!(cache.get(&"a"), None);
+  let mut cache = TtlCache::new(2, Duration::from_secs(60));
+  cache.insert("a", 1);
+  assert_eq!(cache.get(&"a"), Some(1));
+}
+
+#[test]
+fn ttl_cache_capacity() {
+  let mut cache = TtlCache::new(2, Duration::from_secs(60));
+  cache.insert("a", 1);
+  std::thread::sleep(Duration::from_millis(1));
+  cache.insert("b", 2);
+  cache.insert("c", 3);
+  assert_eq!(cache.get(&"a"), None);
+  assert_eq!(cache.get(&"b"), Some(2));
+  assert_eq!(cache.get(&"c"), Some(3));
+}
+
+#[test]
+fn ttl_cache_insert_with_ttl() {
+  let mut cache = TtlCache::new(2, Duration::ZERO);
+  cache.insert_with_ttl("a", 1, Duration::from_secs(60));
+  assert_eq!(cache.get(&"a"), Some(1));
+}
+
+#[test]
+fn response_freshness() {
+  let now = DateTime::parse_from_rfc3339("2021-08-21T20:00:00Z")
+    .unwrap()
+    .with_timezone(&Utc);
+  let expires = Some("Sat, 21 Aug 2021 20:05:00 GMT");
+  assert_eq!(
+    freshness(Some("public, max-age=60, s-maxage=120"), expires, now),
+    Some(Duration::from_secs(60))
+  );
+  assert_eq!(
+    freshness(None, expires, now),
+    Some(Duration::from_secs(300))
+  );
+  assert_eq!(
+    freshness(Some("public"), expires, now),
+    Some(Duration::from_secs(300))
+  );
+  assert_eq!(freshness(Some("no-cache"), expires, now), None);
+  assert_eq!(freshness(Some("max-age=0"), expires, now), None);
+  assert_eq!(
+    freshness(None, Some("Sat, 21 Aug 2021 19:55:00 GMT"), now),
+    None
+  );
+  assert_eq!(freshness(None, None, now), None);
+}
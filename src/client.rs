@@ -0,0 +1,1691 @@
+//! NWS API client
+use crate::cache::{freshness, TtlCache};
+use crate::retry::{parse_retry_after, retry};
+use crate::{
+  find_city, report_bundles, with_units, Alert, AlertCollection, AlertFilter, City, Error, Feature,
+  Forecast, ForecastBundle, GridData, Observation, Office, Page, Point, PointInfo, ProblemDetails,
+  Product, ProductCollection, ProductText, RadarStation, Station, Units, WeatherBundle, Zone,
+  ZoneForecast,
+};
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, Utc};
+use futures::stream::{self, Stream, StreamExt};
+use log::{debug, warn};
+use reqwest::header::{
+  HeaderMap, ACCEPT_LANGUAGE, CACHE_CONTROL, ETAG, EXPIRES, IF_NONE_MATCH, RETRY_AFTER,
+};
+use reqwest::{Client, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Default base URL of the NWS API
+pub const DEFAULT_BASE_URL: &str = "https://api.weather.gov/";
+
+/// Default User-Agent header sent with every request
+pub const DEFAULT_USER_AGENT: &str = concat!("nws/", env!("CARGO_PKG_VERSION"));
+
+/// Default timeout of each request made by a built WeatherClient
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time to live of cached point lookups
+pub const DEFAULT_POINT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Shortest interval WeatherClient::forecast_stream polls at
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most pages of a paginated listing that are followed
+pub const MAX_PAGES: usize = 10;
+
+/// How long a remembered ETag and body are kept for
+const ETAG_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// ETag and body of the last response from each URL
+type EtagCache = TtlCache<String, (String, String)>;
+
+/// NWS API client
+///
+/// Wraps a reqwest Client along with the base URL requests are made
+/// against. Use WeatherClient::builder to configure one. Clones share
+/// the same point, response and ETag caches.
+#[derive(Debug, Clone)]
+pub struct WeatherClient {
+  client: Client,
+  base_url: String,
+  attempts: u32,
+  base_delay: Duration,
+  language: Option<String>,
+  point_cache: Option<Arc<Mutex<TtlCache<String, PointInfo>>>>,
+  response_cache: Option<Arc<Mutex<TtlCache<String, String>>>>,
+  etags: Option<Arc<Mutex<EtagCache>>>,
+}
+
+impl From<Client> for WeatherClient {
+  fn from(client: Client) -> Self {
+    WeatherClient {
+      client,
+      base_url: DEFAULT_BASE_URL.to_string(),
+      attempts: 1,
+      base_delay: Duration::ZERO,
+      language: None,
+      point_cache: None,
+      response_cache: None,
+      etags: None,
+    }
+  }
+}
+
+impl WeatherClient {
+  /// Create a new WeatherClientBuilder
+  pub fn builder() -> WeatherClientBuilder {
+    WeatherClientBuilder::default()
+  }
+
+  /// Cache up to `capacity` point lookups for `ttl` (typically
+  /// DEFAULT_POINT_TTL), keyed by the rounded coordinates. A cache hit
+  /// skips the network entirely.
+  pub fn with_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+    self.point_cache = Some(Arc::new(Mutex::new(TtlCache::new(capacity, ttl))));
+    self
+  }
+
+  /// Cache up to `capacity` response bodies keyed by URL, for as long
+  /// as their Cache-Control max-age or Expires header says they are
+  /// fresh. Responses without either are not cached.
+  pub fn with_response_cache(mut self, capacity: usize) -> Self {
+    self.response_cache = Some(Arc::new(Mutex::new(TtlCache::new(
+      capacity,
+      Duration::ZERO,
+    ))));
+    self
+  }
+
+  /// Remember the ETag of each response and send it as If-None-Match
+  /// when requesting the same URL again. A 304 Not Modified is then
+  /// answered with the remembered body. At most `capacity` URLs are
+  /// remembered, each for a day.
+  pub fn with_etags(mut self, capacity: usize) -> Self {
+    self.etags = Some(Arc::new(Mutex::new(TtlCache::new(capacity, ETAG_TTL))));
+    self
+  }
+
+  /// The base URL requests are made against
+  pub fn base_url(&self) -> &str {
+    &self.base_url
+  }
+
+  /// GET /points/{lat},{lng}
+  ///
+  /// Coordinates are rounded to 4 decimal places, without trailing
+  /// zeros. Returns Error::InvalidCoordinate if they are out of range.
+  /// NWS responds with a 301 to the corrected URL for coordinates it
+  /// doesn't accept as-is, which is followed by the redirect policy of
+  /// the underlying reqwest::Client (the default policy follows up to
+  /// 10 redirects).
+  pub async fn point(&self, pnt: &Point) -> Result<PointInfo, Error> {
+    let url = self.point_url(pnt)?;
+    let cache = match &self.point_cache {
+      Some(cache) => cache,
+      None => return self.get_json(&url).await,
+    };
+    if let Some(res) = cache.lock().unwrap().get(&url) {
+      debug!("point cache hit: {}", url);
+      return Ok(res);
+    }
+    let res: PointInfo = self.get_json(&url).await?;
+    cache.lock().unwrap().insert(url, res.clone());
+    Ok(res)
+  }
+
+  fn point_url(&self, pnt: &Point) -> Result<String, Error> {
+    Ok(format!(
+      "{}points/{}",
+      self.base_url,
+      pnt.nws_coordinates()?
+    ))
+  }
+
+  /// Find the City matching `name` and `state` (case-insensitive) and
+  /// GET its point, returning Error::CityNotFound if there is no match.
+  pub async fn point_by_name(
+    &self,
+    cities: &[City],
+    name: &str,
+    state: &str,
+  ) -> Result<PointInfo, Error> {
+    let city = find_city(cities, name, state).ok_or_else(|| Error::CityNotFound {
+      name: name.to_string(),
+      state: state.to_string(),
+    })?;
+    self.point(&Point::from(city)).await
+  }
+
+  /// GET the forecast of a point
+  pub async fn forecast(&self, pnt: &PointInfo) -> Result<Forecast, Error> {
+    self.forecast_with_units(pnt, None).await
+  }
+
+  /// GET /gridpoints/{office}/{x},{y}/forecast directly, skipping
+  /// the point lookup when the grid is already known (e.g. from
+  /// PointProps::grid_point)
+  pub async fn forecast_by_grid(&self, office: &str, x: u32, y: u32) -> Result<Forecast, Error> {
+    self
+      .get_forecast_json(&self.grid_forecast_url(office, x, y))
+      .await
+  }
+
+  fn grid_forecast_url(&self, office: &str, x: u32, y: u32) -> String {
+    format!(
+      "{}gridpoints/{}/{},{}/forecast",
+      self.base_url, office, x, y
+    )
+  }
+
+  /// GET the forecast of a point, keeping the UTC offset of the
+  /// forecast location rather than converting to Local
+  pub async fn forecast_with_offset(
+    &self,
+    pnt: &PointInfo,
+  ) -> Result<Forecast<FixedOffset>, Error> {
+    self.get_forecast_json(&pnt.properties.forecast).await
+  }
+
+  /// GET the forecast of a point in the given Units, or the API
+  /// default if None
+  pub async fn forecast_with_units(
+    &self,
+    pnt: &PointInfo,
+    units: Option<Units>,
+  ) -> Result<Forecast, Error> {
+    self
+      .get_forecast_json(&with_units(&pnt.properties.forecast, units))
+      .await
+  }
+
+  /// GET the metadata of a point and then its forecast
+  pub async fn forecast_for_point(&self, pnt: &Point) -> Result<Forecast, Error> {
+    let info = self.point(pnt).await?;
+    self.forecast(&info).await
+  }
+
+  /// GET the forecast of a City and bundle them together, returning
+  /// Error::PointNotCovered if NWS has no data for its coordinates.
+  pub async fn weather_bundle(&self, city: City) -> Result<WeatherBundle, Error> {
+    crate::fetch_weather_bundle(city, self).await
+  }
+
+  /// GET the forecasts of many points, running up to `concurrency`
+  /// point and forecast lookups at once. Results are in the same
+  /// order as `points`.
+  pub async fn forecasts(
+    &self,
+    points: &[Point],
+    concurrency: usize,
+  ) -> Vec<Result<Forecast, Error>> {
+    stream::iter(points)
+      .map(|pnt| self.forecast_for_point(pnt))
+      .buffered(concurrency.max(1))
+      .collect()
+      .await
+  }
+
+  /// Poll the forecast of a point every `interval` (at least
+  /// MIN_POLL_INTERVAL), starting immediately. The point is looked up
+  /// once, until a lookup succeeds. Errors are yielded as items and
+  /// polling continues.
+  pub fn forecast_stream(
+    self,
+    pnt: Point,
+    interval: Duration,
+  ) -> impl Stream<Item = Result<Forecast, Error>> {
+    let interval = interval.max(MIN_POLL_INTERVAL);
+    let state = (self, pnt, None, None);
+    stream::unfold(state, move |(client, pnt, ticks, info)| async move {
+      let mut ticks = ticks.unwrap_or_else(|| tokio::time::interval(interval));
+      ticks.tick().await;
+      let info = match info {
+        Some(info) => info,
+        None => match client.point(&pnt).await {
+          Ok(info) => info,
+          Err(e) => return Some((Err(e), (client, pnt, Some(ticks), None))),
+        },
+      };
+      let res = client.forecast(&info).await;
+      Some((res, (client, pnt, Some(ticks), Some(info))))
+    })
+  }
+
+  /// GET the first 10 periods of the hourly forecast of a point
+  pub async fn report(&self, pnt: &Point) -> Result<Vec<ForecastBundle>, Error> {
+    let info = self.point(pnt).await?;
+    let forecast = self.forecast_hourly(&info).await?;
+    Ok(report_bundles(&forecast))
+  }
+
+  /// GET the hourly forecast of a point
+  pub async fn forecast_hourly(&self, pnt: &PointInfo) -> Result<Forecast, Error> {
+    self.forecast_hourly_with_units(pnt, None).await
+  }
+
+  /// GET the hourly forecast of a point in the given Units, or the API
+  /// default if None
+  pub async fn forecast_hourly_with_units(
+    &self,
+    pnt: &PointInfo,
+    units: Option<Units>,
+  ) -> Result<Forecast, Error> {
+    self
+      .get_forecast_json(&with_units(&pnt.properties.forecast_hourly, units))
+      .await
+  }
+
+  /// GET the raw gridpoint forecast of a point
+  pub async fn grid_data(&self, pnt: &PointInfo) -> Result<GridData, Error> {
+    self.get_json(&pnt.properties.forecast_grid_data).await
+  }
+
+  /// GET the observation stations near a point, closest first,
+  /// following up to MAX_PAGES pages
+  pub async fn observation_stations(&self, pnt: &PointInfo) -> Result<Vec<Station>, Error> {
+    self
+      .get_pages(pnt.properties.observation_stations.clone())
+      .await
+  }
+
+  /// GET /stations/{id}/observations?start={start}&end={end}, newest
+  /// first. Pages are followed as in
+  /// WeatherClient::observation_stations.
+  pub async fn observations(
+    &self,
+    station_id: &str,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+  ) -> Result<Vec<Observation>, Error> {
+    let url = format!(
+      "{}{}",
+      self.base_url,
+      observations_path(station_id, start, end)
+    );
+    let features: Vec<Feature<Observation>> = self.get_pages(url).await?;
+    Ok(features.into_iter().map(|f| f.properties).collect())
+  }
+
+  /// GET /stations/{id}/observations/latest
+  pub async fn latest_observation(&self, station_id: &str) -> Result<Observation, Error> {
+    let url = format!(
+      "{}stations/{}/observations/latest",
+      self.base_url, station_id
+    );
+    let res: Feature<Observation> = self.get_json(&url).await?;
+    Ok(res.properties)
+  }
+
+  /// GET /radar/stations/{id}
+  pub async fn radar_station(&self, id: &str) -> Result<RadarStation, Error> {
+    let url = format!("{}radar/stations/{}", self.base_url, id);
+    self.get_json(&url).await
+  }
+
+  /// GET the forecast office of a point
+  pub async fn office(&self, pnt: &PointInfo) -> Result<Office, Error> {
+    self.get_json(&pnt.properties.forecast_office).await
+  }
+
+  /// GET the fire weather zone of a point
+  pub async fn fire_weather_zone(&self, pnt: &PointInfo) -> Result<Zone, Error> {
+    self.zone(&pnt.properties.fire_weather_zone).await
+  }
+
+  /// GET the forecast of a point's fire weather zone
+  pub async fn fire_weather_forecast(&self, pnt: &PointInfo) -> Result<ZoneForecast, Error> {
+    let url = format!("{}/forecast", pnt.properties.fire_weather_zone);
+    self.get_json(&url).await
+  }
+
+  /// GET /products/types/{type}/locations/{office}
+  pub async fn products(&self, product_type: &str, office: &str) -> Result<Vec<Product>, Error> {
+    let url = format!(
+      "{}products/types/{}/locations/{}",
+      self.base_url, product_type, office
+    );
+    let res: ProductCollection = self.get_json(&url).await?;
+    Ok(res.products)
+  }
+
+  /// GET /products/{id}, returning its productText
+  pub async fn product_text(&self, id: &str) -> Result<String, Error> {
+    let url = format!("{}products/{}", self.base_url, id);
+    let res: ProductText = self.get_json(&url).await?;
+    Ok(res.product_text)
+  }
+
+  /// GET the metadata of a zone, given its URL
+  pub async fn zone(&self, url: &str) -> Result<Zone, Error> {
+    let res: Feature<Zone> = self.get_json(url).await?;
+    Ok(res.properties)
+  }
+
+  /// GET /alerts/active?area={state}, given a two-letter state code
+  pub async fn alerts(&self, state: &str) -> Result<Vec<Alert>, Error> {
+    self.alerts_filtered(state, &AlertFilter::new()).await
+  }
+
+  /// GET /alerts/active?area={state} with the severity, urgency and
+  /// event query parameters of an AlertFilter. Only matching alerts are
+  /// returned.
+  pub async fn alerts_filtered(
+    &self,
+    state: &str,
+    filter: &AlertFilter,
+  ) -> Result<Vec<Alert>, Error> {
+    check_state(state)?;
+    let mut url = Url::parse(&format!("{}alerts/active", self.base_url))
+      .map_err(|_| Error::InvalidBaseUrl(self.base_url.clone()))?;
+    url
+      .query_pairs_mut()
+      .append_pair("area", &state.to_ascii_uppercase())
+      .extend_pairs(filter.query());
+    let res: AlertCollection = self.get_json(url.as_str()).await?;
+    Ok(
+      res
+        .features
+        .into_iter()
+        .filter(|a| filter.matches(a))
+        .collect(),
+    )
+  }
+
+  /// GET /alerts/active?point={lat},{lng}, the alerts affecting a
+  /// single point
+  pub async fn alerts_for_point(&self, pnt: &Point) -> Result<Vec<Alert>, Error> {
+    let url = format!(
+      "{}alerts/active?point={}",
+      self.base_url,
+      pnt.nws_coordinates()?
+    );
+    let res: AlertCollection = self.get_json(&url).await?;
+    Ok(res.features)
+  }
+
+  /// GET the features of a paginated listing, following the
+  /// pagination.next link of each page until a page is empty or has
+  /// no next link, up to MAX_PAGES pages
+  async fn get_pages<T: DeserializeOwned>(&self, url: String) -> Result<Vec<T>, Error> {
+    let mut features = Vec::new();
+    let mut url = Some(url);
+    for _ in 0..MAX_PAGES {
+      let Some(next) = url.take() else { break };
+      let res: Page<T> = self.get_json(&next).await?;
+      if res.features.is_empty() {
+        break;
+      }
+      features.extend(res.features);
+      url = res.pagination.and_then(|p| p.next);
+    }
+    Ok(features)
+  }
+
+  /// GET /alerts/active/zone/{zone_id}, given a zone ID such as
+  /// Zone::id (e.g. "NYZ072")
+  pub async fn alerts_for_zone(&self, zone_id: &str) -> Result<Vec<Alert>, Error> {
+    let url = format!("{}alerts/active/zone/{}", self.base_url, zone_id);
+    let res: AlertCollection = self.get_json(&url).await?;
+    Ok(res.features)
+  }
+
+  /// Rewrite an absolute NWS URL returned by the API (e.g.
+  /// PointProps.forecast) to go through base_url, so that a client
+  /// pointed at a mock server follows links to the same server
+  fn resolve_url(&self, url: &str) -> String {
+    match url.strip_prefix(DEFAULT_BASE_URL) {
+      Some(path) if self.base_url != DEFAULT_BASE_URL => format!("{}{}", self.base_url, path),
+      _ => url.to_string(),
+    }
+  }
+
+  /// GET a url and deserialize the JSON response body, returning
+  /// Error::Api if the response status is not a success. Transient
+  /// failures are retried according to WeatherClientBuilder::retry.
+  async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+    let url = self.resolve_url(url);
+    retry(self.attempts, self.base_delay, || {
+      self.get_json_once(&url, None)
+    })
+    .await
+  }
+
+  /// Same as get_json, but sending the Accept-Language set by
+  /// WeatherClientBuilder::language. English is listed as a fallback
+  /// so endpoints without translations still respond.
+  async fn get_forecast_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+    let url = self.resolve_url(url);
+    let language = self
+      .language
+      .as_ref()
+      .map(|language| format!("{}, en;q=0.5", language));
+    retry(self.attempts, self.base_delay, || {
+      self.get_json_once(&url, language.as_deref())
+    })
+    .await
+  }
+
+  async fn get_json_once<T: DeserializeOwned>(
+    &self,
+    url: &str,
+    language: Option<&str>,
+  ) -> Result<T, Error> {
+    if let Some(cache) = &self.response_cache {
+      if let Some(body) = cache.lock().unwrap().get(&url.to_string()) {
+        debug!("response cache hit: {}", url);
+        return decode_body(StatusCode::OK, body);
+      }
+    }
+    debug!("GET {}", url);
+    let mut request = self.client.get(url);
+    if let Some(language) = language {
+      request = request.header(ACCEPT_LANGUAGE, language);
+    }
+    let cached = self
+      .etags
+      .as_ref()
+      .and_then(|etags| etags.lock().unwrap().get(&url.to_string()));
+    if let Some((etag, _)) = &cached {
+      request = request.header(IF_NONE_MATCH, etag);
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    if let (StatusCode::NOT_MODIFIED, Some((_, body))) = (status, cached) {
+      debug!("not modified: {}", url);
+      return decode_body(StatusCode::OK, body);
+    }
+    if let Some(e) = rate_limited(status, response.headers()) {
+      return Err(e);
+    }
+    let headers = response.headers();
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    let ttl = freshness(header(CACHE_CONTROL), header(EXPIRES), Utc::now());
+    let etag = header(ETAG).map(str::to_string);
+    let body = response.text().await?;
+    if let (Some(etags), Some(etag)) = (&self.etags, etag) {
+      if status.is_success() {
+        etags
+          .lock()
+          .unwrap()
+          .insert(url.to_string(), (etag, body.clone()));
+      }
+    }
+    if let (Some(cache), Some(ttl)) = (&self.response_cache, ttl) {
+      if status.is_success() {
+        cache
+          .lock()
+          .unwrap()
+          .insert_with_ttl(url.to_string(), body.clone(), ttl);
+      }
+    }
+    decode_body(status, body)
+  }
+}
+
+#[test]
+fn resolve_url() {
+  let client = WeatherClient::from(Client::new());
+  let url = "https://api.weather.gov/gridpoints/OKX/33,35/forecast";
+  assert_eq!(client.resolve_url(url), url);
+  let client = WeatherClient::builder()
+    .base_url("http://127.0.0.1:8080")
+    .build()
+    .unwrap();
+  assert_eq!(
+    client.resolve_url(url),
+    "http://127.0.0.1:8080/gridpoints/OKX/33,35/forecast"
+  );
+  assert_eq!(
+    client.resolve_url("https://example.com/forecast"),
+    "https://example.com/forecast"
+  );
+}
+
+#[test]
+fn grid_forecast_url() {
+  let client = WeatherClient::from(Client::new());
+  assert_eq!(
+    client.grid_forecast_url("OKX", 33, 35),
+    "https://api.weather.gov/gridpoints/OKX/33,35/forecast"
+  );
+}
+
+#[test]
+fn point_url_https() {
+  let client = WeatherClient::from(Client::new());
+  let url = client.point_url(&Point::new(40.7128, -74.006)).unwrap();
+  assert!(url.starts_with("https://"));
+  assert_eq!(url, "https://api.weather.gov/points/40.7128,-74.006");
+}
+
+/// Whether a User-Agent contains an email-like token
+fn has_contact(user_agent: &str) -> bool {
+  user_agent
+    .split(|c: char| c.is_whitespace() || "(),;".contains(c))
+    .any(|token| matches!(token.split_once('@'), Some((user, host)) if !user.is_empty() && !host.is_empty()))
+}
+
+/// Error::InvalidState unless `state` is two ASCII letters
+pub(crate) fn check_state(state: &str) -> Result<(), Error> {
+  if state.len() != 2 || !state.chars().all(|c| c.is_ascii_alphabetic()) {
+    return Err(Error::InvalidState(state.to_string()));
+  }
+  Ok(())
+}
+
+/// Path of the observations of a station between two times, relative
+/// to the API root
+pub(crate) fn observations_path(
+  station_id: &str,
+  start: DateTime<Local>,
+  end: DateTime<Local>,
+) -> String {
+  format!(
+    "stations/{}/observations?start={}&end={}",
+    station_id,
+    start
+      .with_timezone(&Utc)
+      .to_rfc3339_opts(SecondsFormat::Secs, true),
+    end
+      .with_timezone(&Utc)
+      .to_rfc3339_opts(SecondsFormat::Secs, true)
+  )
+}
+
+/// Error::RateLimited if the response is a 429 with a valid
+/// Retry-After header
+pub(crate) fn rate_limited(status: StatusCode, headers: &HeaderMap) -> Option<Error> {
+  if status != StatusCode::TOO_MANY_REQUESTS {
+    return None;
+  }
+  let retry_after = headers
+    .get(RETRY_AFTER)
+    .and_then(|v| v.to_str().ok())
+    .and_then(|v| parse_retry_after(v, Utc::now()))?;
+  Some(Error::RateLimited { retry_after })
+}
+
+/// Deserialize a JSON response body, returning Error::Api if the
+/// response status is not a success
+pub(crate) fn decode_body<T: DeserializeOwned>(
+  status: StatusCode,
+  body: String,
+) -> Result<T, Error> {
+  debug!("{}", body);
+  if !status.is_success() {
+    return Err(Error::Api {
+      status: status.as_u16(),
+      problem: ProblemDetails::parse(&body).map(Box::new),
+      body,
+    });
+  }
+  Ok(serde_json::from_str(&body)?)
+}
+
+/// Builder for WeatherClient
+///
+/// NWS requires a User-Agent identifying the application, ideally
+/// with contact info, e.g. "(myweatherapp.com, contact@myweatherapp.com)".
+/// Clients without a contact email may be rate limited or blocked, so
+/// WeatherClientBuilder::build warns when it has no '@', including for
+/// the DEFAULT_USER_AGENT.
+#[derive(Debug)]
+pub struct WeatherClientBuilder {
+  user_agent: String,
+  base_url: String,
+  timeout: Option<Duration>,
+  attempts: u32,
+  base_delay: Duration,
+  language: Option<String>,
+}
+
+impl Default for WeatherClientBuilder {
+  fn default() -> Self {
+    WeatherClientBuilder {
+      user_agent: DEFAULT_USER_AGENT.to_string(),
+      base_url: DEFAULT_BASE_URL.to_string(),
+      timeout: Some(DEFAULT_TIMEOUT),
+      attempts: 1,
+      base_delay: Duration::ZERO,
+      language: None,
+    }
+  }
+}
+
+impl WeatherClientBuilder {
+  /// Set the User-Agent header sent with every request. This should
+  /// include a contact email, and a warning is logged on build if it
+  /// doesn't.
+  pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+    self.user_agent = user_agent.into();
+    self
+  }
+
+  /// Set the base URL requests are made against, including the
+  /// scheme (defaults to https). A trailing slash is added if
+  /// missing.
+  pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+    let mut base_url = base_url.into();
+    if !base_url.ends_with('/') {
+      base_url.push('/');
+    }
+    self.base_url = base_url;
+    self
+  }
+
+  /// Set the timeout of each request, after which it fails with
+  /// Error::Timeout. Defaults to DEFAULT_TIMEOUT.
+  pub fn timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = Some(timeout);
+    self
+  }
+
+  /// Let requests wait indefinitely instead of timing out
+  pub fn no_timeout(mut self) -> Self {
+    self.timeout = None;
+    self
+  }
+
+  /// Make up to `attempts` attempts per request, retrying 5xx
+  /// responses and connection errors with exponential backoff
+  /// starting at `base_delay`. Defaults to a single attempt.
+  pub fn retry(mut self, attempts: u32, base_delay: Duration) -> Self {
+    self.attempts = attempts.max(1);
+    self.base_delay = base_delay;
+    self
+  }
+
+  /// Request forecasts in a language such as "es" by setting the
+  /// Accept-Language header. NWS falls back to English where no
+  /// translation is available.
+  pub fn language(mut self, language: impl Into<String>) -> Self {
+    self.language = Some(language.into());
+    self
+  }
+
+  /// Build the WeatherClient
+  pub fn build(self) -> Result<WeatherClient, Error> {
+    if !has_contact(&self.user_agent) {
+      warn!(
+        "User-Agent {:?} has no contact email, NWS may block requests",
+        self.user_agent
+      );
+    }
+    let mut client = Client::builder().user_agent(self.user_agent);
+    if let Some(timeout) = self.timeout {
+      client = client.timeout(timeout);
+    }
+    let client = client.build()?;
+    Ok(WeatherClient {
+      client,
+      base_url: self.base_url,
+      attempts: self.attempts,
+      base_delay: self.base_delay,
+      language: self.language,
+      point_cache: None,
+      response_cache: None,
+      etags: None,
+    })
+  }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn user_agent_header() {
+  use wiremock::matchers::{header, path};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/40.7128,-74.006"))
+    .and(header("user-agent", "myapp/1.0 (ops@example.com)"))
+    .respond_with(ResponseTemplate::new(404))
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .user_agent("myapp/1.0 (ops@example.com)")
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  assert!(client.point(&Point::new(40.7128, -74.006)).await.is_err());
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn point_not_found() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/51.5085,-0.1257"))
+    .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let err = client
+    .point(&Point::new(51.5085, -0.1257))
+    .await
+    .unwrap_err();
+  match err {
+    Error::Api {
+      status,
+      body,
+      problem,
+    } => {
+      assert_eq!(status, 404);
+      assert_eq!(body, "not found");
+      assert_eq!(problem, None);
+    }
+    e => panic!("expected Error::Api, got {:?}", e),
+  }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn service_unavailable() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let body = r#"{
+    "correlationId": "2f3bb5e1",
+    "title": "Service Unavailable",
+    "type": "https://api.weather.gov/problems/ServiceUnavailable",
+    "status": 503,
+    "detail": "The service is temporarily unavailable"
+  }"#;
+  let server = MockServer::start().await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast"))
+    .respond_with(ResponseTemplate::new(503).set_body_string(body))
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let url = format!("{}gridpoints/OKX/33,35/forecast", client.base_url());
+  let err = client.get_json::<Forecast>(&url).await.unwrap_err();
+  assert!(matches!(err, Error::Api { status: 503, .. }));
+  assert_eq!(
+    err.to_string(),
+    "NWS API error 503: The service is temporarily unavailable"
+  );
+  match err {
+    Error::Api {
+      problem: Some(problem),
+      ..
+    } => assert_eq!(problem.correlation_id.as_deref(), Some("2f3bb5e1")),
+    e => panic!("expected Error::Api with a problem, got {:?}", e),
+  }
+}
+
+#[cfg(test)]
+const TEST_OBSERVATION: &str = r#"{
+  "properties": {
+    "timestamp": "2021-08-21T19:51:00+00:00",
+    "temperature": {"unitCode": "wmoUnit:degC", "value": 26.1}
+  }
+}"#;
+
+#[cfg(test)]
+#[tokio::test]
+async fn retry_transient_failures() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/stations/KNYC/observations/latest"))
+    .respond_with(ResponseTemplate::new(503))
+    .up_to_n_times(2)
+    .mount(&server)
+    .await;
+  Mock::given(path("/stations/KNYC/observations/latest"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(TEST_OBSERVATION))
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .retry(3, Duration::from_millis(1))
+    .build()
+    .unwrap();
+  let obs = client.latest_observation("KNYC").await.unwrap();
+  assert_eq!(obs.temperature, Some(26.1));
+  assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn retry_skips_client_errors() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/stations/KNYC/observations/latest"))
+    .respond_with(ResponseTemplate::new(404))
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .retry(3, Duration::from_millis(1))
+    .build()
+    .unwrap();
+  assert!(client.latest_observation("KNYC").await.is_err());
+  assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[cfg(test)]
+#[tokio::test(start_paused = true)]
+async fn rate_limited_response() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  let mock_responses = || async {
+    Mock::given(path("/stations/KNYC/observations/latest"))
+      .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1"))
+      .up_to_n_times(1)
+      .mount(&server)
+      .await;
+    Mock::given(path("/stations/KNYC/observations/latest"))
+      .respond_with(ResponseTemplate::new(200).set_body_string(TEST_OBSERVATION))
+      .mount(&server)
+      .await;
+  };
+  mock_responses().await;
+  // with time paused, any request timeout would fire while waiting on
+  // the mock server
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .no_timeout()
+    .build()
+    .unwrap();
+  match client.latest_observation("KNYC").await.unwrap_err() {
+    Error::RateLimited { retry_after } => assert_eq!(retry_after, Duration::from_secs(1)),
+    e => panic!("expected Error::RateLimited, got {:?}", e),
+  }
+
+  server.reset().await;
+  mock_responses().await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .retry(2, Duration::from_millis(1))
+    .no_timeout()
+    .build()
+    .unwrap();
+  let start = tokio::time::Instant::now();
+  assert!(client.latest_observation("KNYC").await.is_ok());
+  assert!(start.elapsed() >= Duration::from_secs(1));
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn point_cache_hit() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/40.7128,-74.006"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::TEST_POINT))
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap()
+    .with_cache(16, DEFAULT_POINT_TTL);
+  let pnt = Point::new(40.7128, -74.006);
+  let first = client.point(&pnt).await.unwrap();
+  let second = client.point(&pnt).await.unwrap();
+  assert_eq!(first.properties.forecast, second.properties.forecast);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn weather_bundle_ocean_point() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/36,-60"))
+    .respond_with(
+      ResponseTemplate::new(404)
+        .set_body_string(r#"{"title": "Data Unavailable For Requested Point", "status": 404}"#),
+    )
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let city = City {
+    city: "Atlantis".to_string(),
+    state_id: "XX".to_string(),
+    lat: 36.0,
+    lng: -60.0,
+  };
+  assert!(matches!(
+    client.weather_bundle(city).await,
+    Err(Error::PointNotCovered { .. })
+  ));
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn point_by_name() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/40.7128,-74.006"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::TEST_POINT))
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let cities = [
+    City {
+      city: "Chicago".to_string(),
+      state_id: "IL".to_string(),
+      lat: 41.8373,
+      lng: -87.6862,
+    },
+    City {
+      city: "New York".to_string(),
+      state_id: "NY".to_string(),
+      lat: 40.7128,
+      lng: -74.006,
+    },
+  ];
+  let res = client
+    .point_by_name(&cities, "new york", "ny")
+    .await
+    .unwrap();
+  assert_eq!(res.properties.radar_station, "KOKX");
+  assert!(matches!(
+    client.point_by_name(&cities, "Springfield", "IL").await,
+    Err(Error::CityNotFound { .. })
+  ));
+}
+
+/// TEST_POINT with its URLs pointing at a mock server
+#[cfg(test)]
+fn test_point_json(server: &wiremock::MockServer) -> String {
+  crate::TEST_POINT.replace("https://api.weather.gov", &server.uri())
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn report() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/40.7128,-74.006"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(test_point_json(&server)))
+    .mount(&server)
+    .await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast/hourly"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::test_forecast_json(12)))
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let report = client.report(&Point::new(40.7128, -74.006)).await.unwrap();
+  assert_eq!(report.len(), 10);
+  assert_eq!(report[0].temperature, 72);
+  assert_eq!(report[0].short_forecast, "Chance Showers And Thunderstorms");
+}
+
+/// JSON of a GET /alerts response with one alert per severity and
+/// urgency pair
+#[cfg(test)]
+fn test_alerts_json(alerts: &[(&str, &str)]) -> String {
+  let features: Vec<String> = alerts
+    .iter()
+    .enumerate()
+    .map(|(i, (severity, urgency))| {
+      format!(
+        r#"{{
+          "id": "urn:oid:{}",
+          "properties": {{
+            "event": "Flood Warning",
+            "headline": null,
+            "severity": "{}",
+            "urgency": "{}",
+            "certainty": "Likely",
+            "effective": "2021-08-21T18:00:00-04:00",
+            "expires": "2021-08-22T06:00:00-04:00",
+            "areaDesc": "New York (Manhattan)",
+            "description": "Flooding is expected.",
+            "instruction": null
+          }}
+        }}"#,
+        i, severity, urgency
+      )
+    })
+    .collect();
+  format!(r#"{{"features": [{}]}}"#, features.join(","))
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn alerts_filtered() {
+  use crate::{Severity, Urgency};
+  use wiremock::matchers::{path, query_param};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/alerts/active"))
+    .and(query_param("area", "NY"))
+    .and(query_param("severity", "Severe"))
+    .and(query_param("urgency", "Immediate"))
+    .respond_with(
+      ResponseTemplate::new(200).set_body_string(test_alerts_json(&[
+        ("Severe", "Immediate"),
+        ("Minor", "Immediate"),
+        ("Severe", "Future"),
+      ])),
+    )
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let filter = AlertFilter::new()
+    .severity(Severity::Severe)
+    .urgency(Urgency::Immediate);
+  let alerts = client.alerts_filtered("ny", &filter).await.unwrap();
+  assert_eq!(alerts.len(), 1);
+  assert_eq!(alerts[0].id, "urn:oid:0");
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn alerts_filtered_by_event() {
+  use wiremock::matchers::{path, query_param};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  let alerts = test_alerts_json(&[("Extreme", "Immediate"), ("Severe", "Immediate")]).replacen(
+    "Flood Warning",
+    "Tornado Warning",
+    1,
+  );
+  Mock::given(path("/alerts/active"))
+    .and(query_param("area", "KS"))
+    .and(query_param("event", "Tornado Warning"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(alerts))
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let filter = AlertFilter::new().event("Tornado Warning");
+  let alerts = client.alerts_filtered("KS", &filter).await.unwrap();
+  assert_eq!(alerts.len(), 1);
+  assert_eq!(alerts[0].properties.event, "Tornado Warning");
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn alerts_for_point() {
+  use wiremock::matchers::{path, query_param};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/alerts/active"))
+    .and(query_param("point", "40.7128,-74.006"))
+    .respond_with(
+      ResponseTemplate::new(200).set_body_string(test_alerts_json(&[("Moderate", "Expected")])),
+    )
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let alerts = client
+    .alerts_for_point(&Point::new(40.7128, -74.006))
+    .await
+    .unwrap();
+  assert_eq!(alerts.len(), 1);
+  assert_eq!(alerts[0].properties.event, "Flood Warning");
+  assert_eq!(alerts[0].properties.severity, "Moderate");
+  assert!(matches!(
+    client.alerts_for_point(&Point::new(91.0, 0.0)).await,
+    Err(Error::InvalidCoordinate)
+  ));
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn products() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let id = "2f2a3ab4-5e4c-4a8a-9d22-8b1b3c2f6e3d";
+  let server = MockServer::start().await;
+  Mock::given(path("/products/types/AFD/locations/OKX"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+      r#"{{
+        "@context": {{"@version": "1.1"}},
+        "@graph": [{{
+          "@id": "https://api.weather.gov/products/{}",
+          "id": "{}",
+          "wmoCollectiveId": "FXUS61",
+          "issuingOffice": "KOKX",
+          "issuanceTime": "2021-08-21T19:38:00+00:00",
+          "productCode": "AFD",
+          "productName": "Area Forecast Discussion"
+        }}]
+      }}"#,
+      id, id
+    )))
+    .mount(&server)
+    .await;
+  Mock::given(path(format!("/products/{}", id)))
+    .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+      r#"{{
+        "id": "{}",
+        "wmoCollectiveId": "FXUS61",
+        "issuanceTime": "2021-08-21T19:38:00+00:00",
+        "productText": "\n000\nFXUS61 KOKX 211938\nAFDOKX\n"
+      }}"#,
+      id
+    )))
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let products = client.products("AFD", "OKX").await.unwrap();
+  assert_eq!(products.len(), 1);
+  assert_eq!(products[0].id, id);
+  assert_eq!(products[0].wmo_collective_id, "FXUS61");
+  let text = client.product_text(&products[0].id).await.unwrap();
+  assert_eq!(text, "\n000\nFXUS61 KOKX 211938\nAFDOKX\n");
+}
+
+#[cfg(test)]
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[cfg(test)]
+struct TestLogger;
+
+#[cfg(test)]
+impl log::Log for TestLogger {
+  fn enabled(&self, metadata: &log::Metadata) -> bool {
+    metadata.level() <= log::Level::Warn
+  }
+  fn log(&self, record: &log::Record) {
+    if self.enabled(record.metadata()) {
+      WARNINGS.lock().unwrap().push(record.args().to_string());
+    }
+  }
+  fn flush(&self) {}
+}
+
+#[test]
+fn user_agent_contact_warning() {
+  let _ = log::set_logger(&TestLogger);
+  log::set_max_level(log::LevelFilter::Warn);
+  assert!(has_contact("myapp/1.0 (ops@example.com)"));
+  assert!(!has_contact("thunderman"));
+  assert!(!has_contact("myapp @ home"));
+  WeatherClient::builder()
+    .user_agent("bare-agent-test")
+    .build()
+    .unwrap();
+  WeatherClient::builder()
+    .user_agent("contact-agent-test (ops@example.com)")
+    .build()
+    .unwrap();
+  WeatherClient::builder().build().unwrap();
+  let warnings = WARNINGS.lock().unwrap();
+  assert!(warnings.iter().any(|w| w.contains("bare-agent-test")));
+  assert!(!warnings.iter().any(|w| w.contains("contact-agent-test")));
+  assert!(warnings.iter().any(|w| w.contains(DEFAULT_USER_AGENT)));
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn forecasts_in_order() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  for (i, coords) in ["40,-74", "41,-74", "42,-74"].iter().enumerate() {
+    let point = test_point_json(&server).replace("/33,35/", &format!("/{},35/", i));
+    Mock::given(path(format!("/points/{}", coords)))
+      .respond_with(ResponseTemplate::new(200).set_body_string(point))
+      .mount(&server)
+      .await;
+    Mock::given(path(format!("/gridpoints/OKX/{},35/forecast", i)))
+      .respond_with(
+        ResponseTemplate::new(200)
+          .set_body_string(crate::test_forecast_json(i + 1))
+          .set_delay(Duration::from_millis(100 * (3 - i as u64))),
+      )
+      .mount(&server)
+      .await;
+  }
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let points = [
+    Point::new(40.0, -74.0),
+    Point::new(41.0, -74.0),
+    Point::new(42.0, -74.0),
+    Point::new(43.0, -74.0),
+  ];
+  let res = client.forecasts(&points, 2).await;
+  assert_eq!(res.len(), 4);
+  for (i, forecast) in res[..3].iter().enumerate() {
+    assert_eq!(forecast.as_ref().unwrap().properties.periods.len(), i + 1);
+  }
+  assert!(matches!(res[3], Err(Error::Api { status: 404, .. })));
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn observation_stations_pagination() {
+  use wiremock::matchers::{path, query_param};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let station = |id: &str| {
+    format!(
+      r#"{{
+        "geometry": {{"type": "Point", "coordinates": [-73.96, 40.78]}},
+        "properties": {{"stationIdentifier": "{}", "name": "{}"}}
+      }}"#,
+      id, id
+    )
+  };
+  let server = MockServer::start().await;
+  let info: PointInfo = serde_json::from_str(&test_point_json(&server)).unwrap();
+  Mock::given(path("/gridpoints/OKX/33,35/stations"))
+    .and(query_param("cursor", "2"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+      r#"{{"features": [{}], "pagination": {{"next": "{}/gridpoints/OKX/33,35/stations?cursor=3"}}}}"#,
+      station("KLGA"),
+      server.uri()
+    )))
+    .mount(&server)
+    .await;
+  Mock::given(path("/gridpoints/OKX/33,35/stations"))
+    .and(query_param("cursor", "3"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"features": []}"#))
+    .mount(&server)
+    .await;
+  Mock::given(path("/gridpoints/OKX/33,35/stations"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+      r#"{{"features": [{}, {}], "pagination": {{"next": "{}/gridpoints/OKX/33,35/stations?cursor=2"}}}}"#,
+      station("KNYC"),
+      station("KJFK"),
+      server.uri()
+    )))
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let stations = client.observation_stations(&info).await.unwrap();
+  let ids: Vec<&str> = stations
+    .iter()
+    .map(|s| s.station_identifier.as_str())
+    .collect();
+  assert_eq!(ids, ["KNYC", "KJFK", "KLGA"]);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn observations_range() {
+  use wiremock::matchers::{path, query_param};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let observation = |timestamp: &str, temperature: f32| {
+    format!(
+      r#"{{"properties": {{"timestamp": "{}", "temperature": {{"unitCode": "wmoUnit:degC", "value": {}}}}}}}"#,
+      timestamp, temperature
+    )
+  };
+  let server = MockServer::start().await;
+  Mock::given(path("/stations/KNYC/observations"))
+    .and(query_param("start", "2021-08-21T00:00:00Z"))
+    .and(query_param("end", "2021-08-22T00:00:00Z"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+      r#"{{"features": [{}, {}, {}]}}"#,
+      observation("2021-08-21T20:51:00+00:00", 26.1),
+      observation("2021-08-21T19:51:00+00:00", 25.6),
+      observation("2021-08-21T18:51:00+00:00", 25.0)
+    )))
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let at = |s: &str| {
+    DateTime::parse_from_rfc3339(s)
+      .unwrap()
+      .with_timezone(&Local)
+  };
+  let obs = client
+    .observations(
+      "KNYC",
+      at("2021-08-21T00:00:00+00:00"),
+      at("2021-08-22T00:00:00+00:00"),
+    )
+    .await
+    .unwrap();
+  assert_eq!(obs.len(), 3);
+  assert!(obs.windows(2).all(|w| w[0].timestamp > w[1].timestamp));
+  assert_eq!(obs[0].temperature, Some(26.1));
+  assert_eq!(obs[2].temperature, Some(25.0));
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn follow_links_to_base_url() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/40.7128,-74.006"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::TEST_POINT))
+    .mount(&server)
+    .await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::test_forecast_json(2)))
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let info = client.point(&Point::new(40.7128, -74.006)).await.unwrap();
+  assert!(info
+    .properties
+    .forecast
+    .starts_with("https://api.weather.gov/"));
+  let forecast = client.forecast(&info).await.unwrap();
+  assert_eq!(forecast.properties.periods.len(), 2);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn alerts_for_zone() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/alerts/active/zone/NYZ072"))
+    .respond_with(
+      ResponseTemplate::new(200).set_body_string(test_alerts_json(&[
+        ("Moderate", "Expected"),
+        ("Minor", "Future"),
+      ])),
+    )
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let alerts = client.alerts_for_zone("NYZ072").await.unwrap();
+  assert_eq!(alerts.len(), 2);
+  assert_eq!(alerts[1].properties.urgency, "Future");
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn point_redirect() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/40.7128,-74.006"))
+    .respond_with(ResponseTemplate::new(301).insert_header("location", "/points/40.713,-74.006"))
+    .expect(1)
+    .mount(&server)
+    .await;
+  Mock::given(path("/points/40.713,-74.006"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::TEST_POINT))
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let info = client.point(&Point::new(40.7128, -74.006)).await.unwrap();
+  assert!(info
+    .properties
+    .forecast
+    .ends_with("/gridpoints/OKX/33,35/forecast"));
+}
+
+#[cfg(test)]
+#[tokio::test(start_paused = true)]
+async fn forecast_stream() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/40.7128,-74.006"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::TEST_POINT))
+    .expect(1)
+    .mount(&server)
+    .await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast"))
+    .respond_with(ResponseTemplate::new(503))
+    .up_to_n_times(1)
+    .mount(&server)
+    .await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::test_forecast_json(1)))
+    .mount(&server)
+    .await;
+  // paused time auto-advances to the next timer while waiting on the
+  // mock server, which would fire any request timeout
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .no_timeout()
+    .build()
+    .unwrap();
+  let start = tokio::time::Instant::now();
+  let updates = client.forecast_stream(Point::new(40.7128, -74.006), Duration::from_secs(600));
+  futures::pin_mut!(updates);
+  let first = updates.next().await;
+  assert!(
+    matches!(first, Some(Err(Error::Api { status: 503, .. }))),
+    "{:?}",
+    first
+  );
+  let forecast = updates.next().await.unwrap().unwrap();
+  assert_eq!(forecast.properties.periods.len(), 1);
+  assert!(start.elapsed() >= Duration::from_secs(600));
+}
+
+#[test]
+fn forecast_stream_outside_runtime() {
+  // the interval is created on first poll, and a zero interval is
+  // clamped rather than panicking
+  let updates = WeatherClient::from(Client::new())
+    .forecast_stream(Point::new(40.7128, -74.006), Duration::ZERO);
+  drop(updates);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn forecast_for_point() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/40.7128,-74.006"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::TEST_POINT))
+    .expect(1)
+    .mount(&server)
+    .await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::test_forecast_json(3)))
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap();
+  let forecast = client
+    .forecast_for_point(&Point::new(40.7128, -74.006))
+    .await
+    .unwrap();
+  assert_eq!(forecast.properties.periods.len(), 3);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn timeout() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/points/40.7128,-74.006"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .set_body_string(crate::TEST_POINT)
+        .set_delay(Duration::from_millis(500)),
+    )
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .timeout(Duration::from_millis(50))
+    .build()
+    .unwrap();
+  let err = client
+    .point(&Point::new(40.7128, -74.006))
+    .await
+    .unwrap_err();
+  assert!(matches!(err, Error::Timeout), "{:?}", err);
+  assert!(err.is_transient());
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn accept_language() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::test_forecast_json(1)))
+    .mount(&server)
+    .await;
+  for language in [None, Some("es")] {
+    let mut builder = WeatherClient::builder().base_url(server.uri());
+    if let Some(language) = language {
+      builder = builder.language(language);
+    }
+    let client = builder.build().unwrap();
+    client.forecast_by_grid("OKX", 33, 35).await.unwrap();
+  }
+  let requests = server.received_requests().await.unwrap();
+  // wiremock splits header values on commas
+  let accept_language: Vec<Option<Vec<&str>>> = requests
+    .iter()
+    .map(|r| {
+      let values = r.headers.get(&"accept-language".into())?;
+      Some(values.iter().map(|v| v.as_str()).collect())
+    })
+    .collect();
+  assert_eq!(accept_language, vec![None, Some(vec!["es", "en;q=0.5"])]);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn response_cache_max_age() {
+  use wiremock::matchers::path;
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .set_body_string(crate::test_forecast_json(1))
+        .insert_header("cache-control", "public, max-age=60"),
+    )
+    .expect(1)
+    .mount(&server)
+    .await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast/hourly"))
+    .respond_with(ResponseTemplate::new(200).set_body_string(crate::test_forecast_json(1)))
+    .expect(2)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap()
+    .with_response_cache(16);
+  for _ in 0..2 {
+    client.forecast_by_grid("OKX", 33, 35).await.unwrap();
+    let url = format!("{}/gridpoints/OKX/33,35/forecast/hourly", server.uri());
+    let _: Forecast = client.get_json(&url).await.unwrap();
+  }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn etag_not_modified() {
+  use wiremock::matchers::{header, path};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  let server = MockServer::start().await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast"))
+    .and(header("if-none-match", "\"abc123\""))
+    .respond_with(ResponseTemplate::new(304))
+    .expect(1)
+    .mount(&server)
+    .await;
+  Mock::given(path("/gridpoints/OKX/33,35/forecast"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .set_body_string(crate::test_forecast_json(2))
+        .insert_header("etag", "\"abc123\""),
+    )
+    .expect(1)
+    .mount(&server)
+    .await;
+  let client = WeatherClient::builder()
+    .base_url(server.uri())
+    .build()
+    .unwrap()
+    .with_etags(16);
+  let first = client.forecast_by_grid("OKX", 33, 35).await.unwrap();
+  let second = client.forecast_by_grid("OKX", 33, 35).await.unwrap();
+  assert_eq!(first.properties.periods.len(), 2);
+  assert_eq!(second.properties.periods.len(), 2);
+}
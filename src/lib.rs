@@ -1,10 +1,221 @@
 //! NWS
-use crate::Error;
-use chrono::{DateTime, Local};
-use log::debug;
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, TimeZone};
+use log::{debug, warn};
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use thiserror::Error;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod client;
+pub mod retry;
+pub mod source;
+#[cfg(feature = "sled")]
+pub mod store;
+pub mod units;
+pub use client::{WeatherClient, WeatherClientBuilder};
+pub use retry::retry;
+pub use source::{FixtureSource, WeatherSource};
+
+/// Errors returned by NWS requests
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+  /// The request could not be sent or its response could not be read
+  #[error(transparent)]
+  Http(reqwest::Error),
+  /// The request did not complete within the client's timeout
+  #[error("request to NWS timed out")]
+  Timeout,
+  /// A file could not be read or written
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
+  /// The embedded database failed
+  #[cfg(feature = "sled")]
+  #[error(transparent)]
+  Sled(#[from] sled::Error),
+  /// A CSV dataset could not be read or parsed
+  #[error(transparent)]
+  Csv(#[from] csv::Error),
+  /// The response body did not match the expected schema
+  #[error(transparent)]
+  Json(#[from] serde_json::Error),
+  /// A latitude or longitude was out of range or malformed
+  #[error("invalid coordinate")]
+  InvalidCoordinate,
+  /// A wind direction was not one of the 16 compass points
+  #[error("invalid compass direction: {0:?}")]
+  InvalidDirection(String),
+  /// The client's base URL could not be parsed
+  #[error("invalid base URL: {0:?}")]
+  InvalidBaseUrl(String),
+  /// The state code was not two ASCII letters
+  #[error("invalid state code: {0:?}")]
+  InvalidState(String),
+  /// A gridpoint validTime was not a "{start}/{duration}" interval
+  #[error("invalid validTime: {0:?}")]
+  InvalidValidTime(String),
+  /// NWS responded with a non-success status. `problem` is the parsed
+  /// body when it is a problem-details document, and the message uses
+  /// its `detail` field, otherwise the raw body.
+  #[error("NWS API error {status}: {}", problem.as_ref().and_then(|p| p.detail.as_deref()).unwrap_or(body))]
+  Api {
+    status: u16,
+    body: String,
+    problem: Option<Box<ProblemDetails>>,
+  },
+  /// No City matched the given name and state
+  #[error("city not found: {name}, {state}")]
+  CityNotFound { name: String, state: String },
+  /// NWS has no data for the point, typically because it is outside
+  /// the US or in the ocean
+  #[error("NWS has no data for point {lat},{lng}")]
+  PointNotCovered { lat: f32, lng: f32 },
+  /// NWS responded with 429 Too Many Requests and a Retry-After
+  /// header
+  #[error("rate limited by NWS, retry after {retry_after:?}")]
+  RateLimited { retry_after: std::time::Duration },
+}
+
+impl Error {
+  /// Whether the error is likely to succeed on retry: a 5xx response,
+  /// rate limiting, a connection failure, or a timeout.
+  pub fn is_transient(&self) -> bool {
+    match self {
+      Error::Api { status, .. } => *status >= 500,
+      Error::RateLimited { .. } => true,
+      Error::Http(e) => e.is_connect(),
+      Error::Timeout => true,
+      _ => false,
+    }
+  }
+}
+
+impl From<reqwest::Error> for Error {
+  fn from(e: reqwest::Error) -> Self {
+    if e.is_timeout() {
+      Error::Timeout
+    } else {
+      Error::Http(e)
+    }
+  }
+}
+
+/// Body of an NWS error response (application/problem+json)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+  /// URI identifying the problem type
+  #[serde(rename(deserialize = "type"))]
+  pub problem_type: Option<String>,
+  pub title: Option<String>,
+  pub status: Option<u16>,
+  pub detail: Option<String>,
+  pub instance: Option<String>,
+  /// ID of the failed request, which NWS support asks for when
+  /// reporting issues
+  #[serde(rename(deserialize = "correlationId"))]
+  pub correlation_id: Option<String>,
+}
+
+impl ProblemDetails {
+  /// Parse a problem-details body, or None if it isn't one
+  pub fn parse(body: &str) -> Option<Self> {
+    let problem: ProblemDetails = serde_json::from_str(body).ok()?;
+    if problem.title.is_none() && problem.detail.is_none() {
+      return None;
+    }
+    Some(problem)
+  }
+}
+
+#[test]
+fn problem_details() {
+  let problem = ProblemDetails::parse(
+    r#"{
+      "correlationId": "1a2b3c4d",
+      "title": "Invalid Parameter",
+      "type": "https://api.weather.gov/problems/InvalidParameter",
+      "status": 400,
+      "detail": "Parameter \"point\" is invalid: '91,0' does not appear to be a valid coordinate",
+      "instance": "https://api.weather.gov/requests/1a2b3c4d"
+    }"#,
+  )
+  .unwrap();
+  assert_eq!(problem.correlation_id.as_deref(), Some("1a2b3c4d"));
+  assert_eq!(problem.title.as_deref(), Some("Invalid Parameter"));
+  assert_eq!(
+    problem.problem_type.as_deref(),
+    Some("https://api.weather.gov/problems/InvalidParameter")
+  );
+  assert_eq!(problem.status, Some(400));
+  assert!(problem.detail.unwrap().starts_with("Parameter \"point\""));
+  assert_eq!(ProblemDetails::parse("not found"), None);
+  assert_eq!(ProblemDetails::parse(r#"{"features": []}"#), None);
+}
+
+/// Latitude in degrees, validated to be within -90..=90
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "f32", into = "f32")]
+pub struct Latitude(f32);
+
+/// Longitude in degrees, validated to be within -180..=180
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "f32", into = "f32")]
+pub struct Longitude(f32);
+
+impl TryFrom<f32> for Latitude {
+  type Error = Error;
+  fn try_from(lat: f32) -> Result<Self, Self::Error> {
+    if (-90.0..=90.0).contains(&lat) {
+      Ok(Latitude(lat))
+    } else {
+      Err(Error::InvalidCoordinate)
+    }
+  }
+}
+
+impl TryFrom<f32> for Longitude {
+  type Error = Error;
+  fn try_from(lng: f32) -> Result<Self, Self::Error> {
+    if (-180.0..=180.0).contains(&lng) {
+      Ok(Longitude(lng))
+    } else {
+      Err(Error::InvalidCoordinate)
+    }
+  }
+}
+
+impl From<Latitude> for f32 {
+  fn from(lat: Latitude) -> Self {
+    lat.0
+  }
+}
+
+impl From<Longitude> for f32 {
+  fn from(lng: Longitude) -> Self {
+    lng.0
+  }
+}
+
+impl Latitude {
+  /// Degrees of the Latitude
+  pub fn degrees(self) -> f32 {
+    self.0
+  }
+}
+
+impl Longitude {
+  /// Degrees of the Longitude
+  pub fn degrees(self) -> f32 {
+    self.0
+  }
+}
 
 /// Geo-coordinate Point object type
 #[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
@@ -13,16 +224,58 @@ pub struct Point {
   pub lng: f32,
 }
 
+/// Approximate (south, north, west, east) bounds of the regions NWS
+/// forecasts, used by Point::is_in_nws_coverage
+const COVERAGE_BOXES: [(f32, f32, f32, f32); 7] = [
+  // contiguous US
+  (24.4, 49.4, -125.0, -66.9),
+  // Alaska, with the Aleutians west of the antimeridian
+  (51.2, 71.5, -180.0, -129.9),
+  (51.2, 53.1, 172.4, 180.0),
+  // Hawaii
+  (18.9, 22.3, -160.3, -154.8),
+  // Puerto Rico and the US Virgin Islands
+  (17.6, 18.6, -67.3, -64.5),
+  // Guam and the Northern Mariana Islands
+  (13.2, 20.6, 144.6, 146.1),
+  // American Samoa
+  (-14.6, -11.0, -171.1, -168.1),
+];
+
 impl Point {
-  /// Create a new Point from (f32, f32)
+  /// Create a new Point from (f32, f32). The coordinates are not
+  /// validated, use Point::from_coordinates for untrusted input.
   pub fn new(lat: f32, lng: f32) -> Self {
-    Point { lat: lat, lng: lng }
+    Point { lat, lng }
+  }
+
+  /// Create a new Point from a validated Latitude and Longitude, which
+  /// unlike Point::new can't be called with the arguments swapped
+  pub fn from_coordinates(lat: Latitude, lng: Longitude) -> Self {
+    Point::new(lat.degrees(), lng.degrees())
+  }
+
+  /// Format the Point as "{lat},{lng}" rounded to the 4 decimal places
+  /// NWS expects, without trailing zeros (which NWS answers with a
+  /// redirect), returning Error::InvalidCoordinate if lat is outside
+  /// -90..=90 or lng is outside -180..=180.
+  fn nws_coordinates(&self) -> Result<String, Error> {
+    Latitude::try_from(self.lat)?;
+    Longitude::try_from(self.lng)?;
+    let trim = |x: f32| {
+      let s = format!("{:.4}", x);
+      match s.trim_end_matches('0').trim_end_matches('.') {
+        "-0" => "0".to_string(),
+        s => s.to_string(),
+      }
+    };
+    Ok(format!("{},{}", trim(self.lat), trim(self.lng)))
   }
 
   /// Given an additional Point, and assuming Points are on Earth,
   /// returns the distance in kilometers between them using the
   /// Haversine formula
-  pub fn earth_distance_from(&self, other: Point) -> f32 {
+  pub fn earth_distance_from(&self, other: &Point) -> f32 {
     let earth_radius_kilometer = 6371.0_f32;
     let lat_rads = self.lat.to_radians();
     let other_lat_rads = other.lat.to_radians();
@@ -36,34 +289,410 @@ impl Point {
 
     let distance = earth_radius_kilometer * central_angle;
 
-    println!(
+    debug!(
       "Distance between points on the surface of Earth is {:.1} kilometers",
       distance
     );
 
     distance
   }
+
+  /// Same as earth_distance_from, but computed in f64 to reduce
+  /// rounding error
+  pub fn earth_distance_from_f64(&self, other: &Point) -> f64 {
+    let earth_radius_kilometer = 6371.0_f64;
+    let (lat, lng) = (f64::from(self.lat), f64::from(self.lng));
+    let (other_lat, other_lng) = (f64::from(other.lat), f64::from(other.lng));
+    let lat_rads = lat.to_radians();
+    let other_lat_rads = other_lat.to_radians();
+
+    let delta_latitude = (lat - other_lat).to_radians();
+    let delta_longitude = (lng - other_lng).to_radians();
+
+    let central_angle_inner = (delta_latitude / 2.0).sin().powi(2)
+      + lat_rads.cos() * other_lat_rads.cos() * (delta_longitude / 2.0).sin().powi(2);
+    let central_angle = 2.0 * central_angle_inner.sqrt().asin();
+
+    earth_radius_kilometer * central_angle
+  }
+
+  /// Whether both coordinates are within `epsilon` degrees of
+  /// another Point's
+  pub fn approx_eq(&self, other: &Point, epsilon: f32) -> bool {
+    (self.lat - other.lat).abs() <= epsilon && (self.lng - other.lng).abs() <= epsilon
+  }
+
+  /// Given an additional Point, returns the initial compass bearing
+  /// in degrees (0..360) of the great-circle path towards it, using
+  /// the forward azimuth formula. Identical points return 0.0.
+  pub fn bearing_to(&self, other: &Point) -> f32 {
+    if self == other {
+      return 0.0;
+    }
+    let lat_rads = self.lat.to_radians();
+    let other_lat_rads = other.lat.to_radians();
+    let delta_longitude = (other.lng - self.lng).to_radians();
+
+    let y = delta_longitude.sin() * other_lat_rads.cos();
+    let x = lat_rads.cos() * other_lat_rads.sin()
+      - lat_rads.sin() * other_lat_rads.cos() * delta_longitude.cos();
+
+    (y.atan2(x).to_degrees() + 360.0) % 360.0
+  }
+
+  /// Geographic midpoint of the great-circle path towards another
+  /// Point. Same as interpolate with `t` = 0.5.
+  pub fn midpoint(&self, other: &Point) -> Point {
+    self.interpolate(other, 0.5)
+  }
+
+  /// Point a fraction `t` (0.0..=1.0) of the way along the
+  /// great-circle path towards another Point, by spherical linear
+  /// interpolation of their unit vectors. The path between antipodal
+  /// points is undefined, and this Point is returned.
+  pub fn interpolate(&self, other: &Point, t: f32) -> Point {
+    let to_vector = |p: &Point| {
+      let (lat, lng) = (f64::from(p.lat).to_radians(), f64::from(p.lng).to_radians());
+      [lat.cos() * lng.cos(), lat.cos() * lng.sin(), lat.sin()]
+    };
+    let (a, b) = (to_vector(self), to_vector(other));
+    let dot = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2]).clamp(-1.0, 1.0);
+    let omega = dot.acos();
+    if omega.sin().abs() < 1e-9 {
+      return Point::new(self.lat, self.lng);
+    }
+    let t = f64::from(t);
+    let wa = ((1.0 - t) * omega).sin() / omega.sin();
+    let wb = (t * omega).sin() / omega.sin();
+    let [x, y, z] = [0, 1, 2].map(|i| wa * a[i] + wb * b[i]);
+    Point::new(
+      z.atan2(x.hypot(y)).to_degrees() as f32,
+      y.atan2(x).to_degrees() as f32,
+    )
+  }
+
+  /// Rough check that the Point is within NWS coverage, so a request
+  /// that would return 404 can be skipped. This is a heuristic using
+  /// COVERAGE_BOXES: points near borders and coasts may be wrong
+  /// either way.
+  pub fn is_in_nws_coverage(&self) -> bool {
+    COVERAGE_BOXES.iter().any(|&(south, north, west, east)| {
+      (south..=north).contains(&self.lat) && (west..=east).contains(&self.lng)
+    })
+  }
+
+  /// Returns the (southwest, northeast) corners of a box containing
+  /// the circle of `radius_km` around this Point. The longitude delta
+  /// is widened by latitude, and latitudes are clamped to the poles.
+  /// A box reaching a pole spans all longitudes. Boxes crossing the
+  /// antimeridian are not wrapped.
+  pub fn bounding_box(&self, radius_km: f32) -> (Point, Point) {
+    let earth_radius_kilometer = 6371.0_f32;
+    let delta_latitude = (radius_km / earth_radius_kilometer).to_degrees();
+    let south = self.lat - delta_latitude;
+    let north = self.lat + delta_latitude;
+    if south <= -90.0 || north >= 90.0 {
+      return (
+        Point::new(south.max(-90.0), -180.0),
+        Point::new(north.min(90.0), 180.0),
+      );
+    }
+    let delta_longitude = (delta_latitude / self.lat.to_radians().cos()).min(180.0);
+    (
+      Point::new(south, self.lng - delta_longitude),
+      Point::new(north, self.lng + delta_longitude),
+    )
+  }
+}
+
+#[test]
+fn bounding_box_contains_radius() {
+  let center = Point::new(40.7128, -74.006);
+  let radius = 50.0;
+  let (sw, ne) = center.bounding_box(radius);
+  assert!(sw.lat < center.lat && sw.lng < center.lng);
+  assert!(ne.lat > center.lat && ne.lng > center.lng);
+  // edges are radius_km away, corners about radius_km * sqrt(2)
+  let north = Point::new(ne.lat, center.lng);
+  let east = Point::new(center.lat, ne.lng);
+  assert!((center.earth_distance_from(&north) - radius).abs() < 0.1);
+  assert!(center.earth_distance_from(&east) >= radius - 0.1);
+  assert!(center.earth_distance_from(&ne) <= radius * 2.0_f32.sqrt());
+}
+
+#[test]
+fn bounding_box_pole() {
+  // 200km from the pole, so a 300km circle covers it
+  let (sw, ne) = Point::new(88.2, 10.0).bounding_box(300.0);
+  assert_eq!(ne, Point::new(90.0, 180.0));
+  assert_eq!(sw.lng, -180.0);
+  assert!(sw.lat < 88.2 && sw.lat > 85.0);
+  let (sw, ne) = Point::new(-88.2, 10.0).bounding_box(300.0);
+  assert_eq!(sw, Point::new(-90.0, -180.0));
+  assert_eq!(ne.lng, 180.0);
+}
+
+#[test]
+fn midpoint() {
+  let london = Point::new(51.5074, -0.1278);
+  let paris = Point::new(48.8566, 2.3522);
+  // reference computed with the spherical midpoint formula
+  assert!(london
+    .midpoint(&paris)
+    .approx_eq(&Point::new(50.1886, 1.1466), 1e-3));
+  let nyc = Point::new(40.7128, -74.006);
+  let la = Point::new(34.0522, -118.2437);
+  assert!(nyc
+    .midpoint(&la)
+    .approx_eq(&Point::new(39.5103, -97.1601), 1e-3));
+  // across the antimeridian, unlike the average of -179 and 179
+  let mid = Point::new(0.0, 179.0).midpoint(&Point::new(0.0, -179.0));
+  assert!(mid.lat.abs() < 1e-3 && (mid.lng.abs() - 180.0).abs() < 1e-3);
+}
+
+#[test]
+fn interpolate_endpoints() {
+  let nyc = Point::new(40.7128, -74.006);
+  let la = Point::new(34.0522, -118.2437);
+  assert!(nyc.interpolate(&la, 0.0).approx_eq(&nyc, 1e-4));
+  assert!(nyc.interpolate(&la, 1.0).approx_eq(&la, 1e-4));
+  let quarter = nyc.interpolate(&la, 0.25);
+  let total = nyc.earth_distance_from(&la);
+  assert!((nyc.earth_distance_from(&quarter) - total / 4.0).abs() < 0.5);
+  assert_eq!(nyc.interpolate(&nyc, 0.5), nyc);
+}
+
+#[test]
+fn nws_coverage() {
+  assert!(Point::new(40.7128, -74.006).is_in_nws_coverage());
+  assert!(Point::new(21.3069, -157.8583).is_in_nws_coverage());
+  assert!(Point::new(61.2181, -149.9003).is_in_nws_coverage());
+  assert!(Point::new(13.4443, 144.7937).is_in_nws_coverage());
+  assert!(!Point::new(51.5074, -0.1278).is_in_nws_coverage());
+  assert!(!Point::new(0.0, 0.0).is_in_nws_coverage());
+}
+
+#[test]
+fn bearing_cardinal_directions() {
+  let origin = Point::new(40.0, -74.0);
+  let approx = |a: f32, b: f32| (a - b).abs() < 0.01;
+  assert!(approx(origin.bearing_to(&Point::new(41.0, -74.0)), 0.0));
+  assert!(approx(origin.bearing_to(&Point::new(39.0, -74.0)), 180.0));
+  assert!(approx(origin.bearing_to(&Point::new(40.0, -74.01)), 270.0));
+  assert!(approx(
+    Point::new(0.0, 0.0).bearing_to(&Point::new(0.0, 1.0)),
+    90.0
+  ));
+  assert_eq!(origin.bearing_to(&Point::new(40.0, -74.0)), 0.0);
+}
+
+#[test]
+fn london_to_paris_f64() {
+  let london = Point::new(51.5074, -0.1278);
+  let paris = Point::new(48.8566, 2.3522);
+  // reference computed with the Haversine formula in f64, R = 6371 km
+  let reference = 343.556_060_f64;
+  let distance = london.earth_distance_from_f64(&paris);
+  assert!((distance - reference).abs() < 1e-3);
+  let distance_f32 = f64::from(london.earth_distance_from(&paris));
+  assert!((distance - distance_f32).abs() < 1e-1);
 }
 
 #[test]
 fn london_to_paris() {
   assert_eq!(
     Point::new(48.85341_f32, -2.34880_f32)
-      .earth_distance_from(Point::new(51.50853_f32, -0.12574_f32)),
+      .earth_distance_from(&Point::new(51.50853_f32, -0.12574_f32)),
     334.9559_f32,
   );
 }
 
+#[test]
+fn nws_coordinates_rounding() {
+  assert_eq!(
+    Point::new(47.123456, -122.0).nws_coordinates().unwrap(),
+    "47.1235,-122"
+  );
+  assert_eq!(
+    Point::new(40.7128, -74.006).nws_coordinates().unwrap(),
+    "40.7128,-74.006"
+  );
+  assert_eq!(
+    Point::new(-0.00001, 10.5).nws_coordinates().unwrap(),
+    "0,10.5"
+  );
+}
+
+#[test]
+fn nws_coordinates_out_of_range() {
+  assert!(matches!(
+    Point::new(91.0, 0.0).nws_coordinates(),
+    Err(Error::InvalidCoordinate)
+  ));
+  assert!(matches!(
+    Point::new(0.0, -180.5).nws_coordinates(),
+    Err(Error::InvalidCoordinate)
+  ));
+}
+
+#[test]
+fn point_approx_eq() {
+  let a = Point::new(40.7128, -74.006);
+  let b = Point::new(40.71281, -74.00601);
+  assert!(a.approx_eq(&b, 1e-4));
+  assert_ne!(a, b);
+  assert!(!a.approx_eq(&Point::new(40.7138, -74.006), 1e-4));
+}
+
+#[test]
+fn latitude_longitude() {
+  let lat = Latitude::try_from(40.7128).unwrap();
+  let lng = Longitude::try_from(-74.006).unwrap();
+  assert_eq!(
+    Point::from_coordinates(lat, lng),
+    Point::new(40.7128, -74.006)
+  );
+  assert!(Latitude::try_from(-74.006).is_ok());
+  assert!(matches!(
+    Latitude::try_from(-100.0),
+    Err(Error::InvalidCoordinate)
+  ));
+  assert!(matches!(
+    Longitude::try_from(180.5),
+    Err(Error::InvalidCoordinate)
+  ));
+  assert!(Latitude::try_from(f32::NAN).is_err());
+  // GeoJSON coordinates are in (lng, lat) order
+  let geometry: PointGeometry =
+    serde_json::from_str(r#"{"type": "Point", "coordinates": [-74.006, 40.7128]}"#).unwrap();
+  assert_eq!(Point::from(geometry), Point::new(40.7128, -74.006));
+  // 100 is a valid longitude but not a valid latitude
+  assert!(serde_json::from_str::<PointGeometry>(
+    r#"{"type": "Point", "coordinates": [40.7128, 100.0]}"#
+  )
+  .is_err());
+}
+
+/// Format as "lat,lng" with 4 fixed decimal places, which FromStr
+/// parses back. Request URLs use nws_coordinates instead, which also
+/// trims trailing zeros.
+impl fmt::Display for Point {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:.4},{:.4}", self.lat, self.lng)
+  }
+}
+
+#[test]
+fn point_display_round_trip() {
+  let point = Point::new(47.123456, -122.0);
+  assert_eq!(point.to_string(), "47.1235,-122.0000");
+  let parsed: Point = point.to_string().parse().unwrap();
+  assert!(parsed.approx_eq(&point, 1e-4));
+  let point = Point::new(-14.27, 170.7);
+  let parsed: Point = point.to_string().parse().unwrap();
+  assert_eq!(parsed, point);
+}
+
+/// Parse a "lat,lng" string, e.g. "40.7128,-74.0060"
+impl std::str::FromStr for Point {
+  type Err = Error;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (lat, lng) = s.split_once(',').ok_or(Error::InvalidCoordinate)?;
+    let lat = lat.trim().parse().map_err(|_| Error::InvalidCoordinate)?;
+    let lng = lng.trim().parse().map_err(|_| Error::InvalidCoordinate)?;
+    let point = Point::new(lat, lng);
+    point.nws_coordinates()?;
+    Ok(point)
+  }
+}
+
+#[test]
+fn point_from_str() {
+  assert_eq!(
+    "40.7128,-74.0060".parse::<Point>().unwrap(),
+    Point::new(40.7128, -74.006)
+  );
+  assert_eq!(
+    " 40.7128 , -74.0060 ".parse::<Point>().unwrap(),
+    Point::new(40.7128, -74.006)
+  );
+  assert!(matches!(
+    "north,west".parse::<Point>(),
+    Err(Error::InvalidCoordinate)
+  ));
+  assert!(matches!(
+    "40.7128".parse::<Point>(),
+    Err(Error::InvalidCoordinate)
+  ));
+  assert!(matches!(
+    "91,0".parse::<Point>(),
+    Err(Error::InvalidCoordinate)
+  ));
+}
+
+/// Convert a (lat, lng) tuple. Note that GeoJSON coordinates are in
+/// the opposite (lng, lat) order.
+impl From<(f32, f32)> for Point {
+  fn from((lat, lng): (f32, f32)) -> Self {
+    Point::new(lat, lng)
+  }
+}
+
+/// Convert a [lat, lng] array. Note that GeoJSON coordinates are in
+/// the opposite [lng, lat] order.
+impl From<[f32; 2]> for Point {
+  fn from([lat, lng]: [f32; 2]) -> Self {
+    Point::new(lat, lng)
+  }
+}
+
+#[test]
+fn point_from_tuple() {
+  assert_eq!(
+    Point::from((40.7128, -74.006)),
+    Point::new(40.7128, -74.006)
+  );
+}
+
+#[test]
+fn point_from_array() {
+  assert_eq!(
+    Point::from([40.7128, -74.006]),
+    Point::new(40.7128, -74.006)
+  );
+}
+
 impl From<City> for Point {
   fn from(city: City) -> Self {
-    Point::new(city.lat city.lng)
+    Point::new(city.lat, city.lng)
+  }
+}
+
+impl From<&City> for Point {
+  fn from(city: &City) -> Self {
+    Point::new(city.lat, city.lng)
   }
 }
 
+#[test]
+fn city_to_point() {
+  let city = City {
+    city: "New York".to_string(),
+    state_id: "NY".to_string(),
+    lat: 40.6943,
+    lng: -73.9249,
+  };
+  let borrowed = Point::from(&city);
+  assert_eq!(borrowed.lat, 40.6943);
+  assert_eq!(borrowed.lng, -73.9249);
+  let owned = Point::from(city);
+  assert_eq!(owned, borrowed);
+}
+
 /// City object
 ///
 /// Used to parse City metadata from datasets acquired on the internet
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct City {
   pub city: String,
   pub state_id: String,
@@ -74,25 +703,224 @@ pub struct City {
 impl City {
   /// Convert a City to Point.
   ///
-  /// Returns Ok(Point) on success. Note that only f32 values are
-  /// accepted (0. 1. -- not 0 1).
-  pub fn into_point(&self) -> Result<Point, std::error::Error> {
-    Ok(Point {
-      lat: self.lat,
-      lng: self.lng,
-    })
+  /// Equivalent to `Point::from(city)`. Note that only f32 values
+  /// are accepted (0. 1. -- not 0 1).
+  pub fn into_point(self) -> Point {
+    Point::from(self)
+  }
+
+  /// Parse Cities from a CSV dataset with a header row containing at
+  /// least the columns city, state_id, lat and lng (e.g. the
+  /// SimpleMaps uscities.csv). Extra columns are ignored.
+  pub fn from_csv_reader<R: std::io::Read>(r: R) -> Result<Vec<City>, Error> {
+    let mut reader = csv::Reader::from_reader(r);
+    let cities = reader.deserialize().collect::<Result<_, _>>()?;
+    Ok(cities)
   }
 }
 
+#[test]
+fn cities_from_csv() {
+  let data = r#""city","city_ascii","state_id","state_name","lat","lng","population"
+"New York","New York","NY","New York","40.6943","-73.9249","18713220"
+"Los Angeles","Los Angeles","CA","California","34.1139","-118.4068","12750807"
+"#;
+  let cities = City::from_csv_reader(data.as_bytes()).unwrap();
+  assert_eq!(cities.len(), 2);
+  assert_eq!(cities[0].city, "New York");
+  assert_eq!(cities[1].state_id, "CA");
+  assert_eq!(Point::from(&cities[1]), Point::new(34.1139, -118.4068));
+}
+
+#[test]
+fn city_into_point() {
+  let city = City {
+    city: "Chicago".to_string(),
+    state_id: "IL".to_string(),
+    lat: 41.8373,
+    lng: -87.6862,
+  };
+  let expected = Point::from(&city);
+  assert_eq!(city.into_point(), expected);
+}
+
+/// Names and two-letter codes of the 50 states, DC and the inhabited
+/// territories
+const STATES: [(&str, &str); 56] = [
+  ("Alabama", "AL"),
+  ("Alaska", "AK"),
+  ("Arizona", "AZ"),
+  ("Arkansas", "AR"),
+  ("California", "CA"),
+  ("Colorado", "CO"),
+  ("Connecticut", "CT"),
+  ("Delaware", "DE"),
+  ("Florida", "FL"),
+  ("Georgia", "GA"),
+  ("Hawaii", "HI"),
+  ("Idaho", "ID"),
+  ("Illinois", "IL"),
+  ("Indiana", "IN"),
+  ("Iowa", "IA"),
+  ("Kansas", "KS"),
+  ("Kentucky", "KY"),
+  ("Louisiana", "LA"),
+  ("Maine", "ME"),
+  ("Maryland", "MD"),
+  ("Massachusetts", "MA"),
+  ("Michigan", "MI"),
+  ("Minnesota", "MN"),
+  ("Mississippi", "MS"),
+  ("Missouri", "MO"),
+  ("Montana", "MT"),
+  ("Nebraska", "NE"),
+  ("Nevada", "NV"),
+  ("New Hampshire", "NH"),
+  ("New Jersey", "NJ"),
+  ("New Mexico", "NM"),
+  ("New York", "NY"),
+  ("North Carolina", "NC"),
+  ("North Dakota", "ND"),
+  ("Ohio", "OH"),
+  ("Oklahoma", "OK"),
+  ("Oregon", "OR"),
+  ("Pennsylvania", "PA"),
+  ("Rhode Island", "RI"),
+  ("South Carolina", "SC"),
+  ("South Dakota", "SD"),
+  ("Tennessee", "TN"),
+  ("Texas", "TX"),
+  ("Utah", "UT"),
+  ("Vermont", "VT"),
+  ("Virginia", "VA"),
+  ("Washington", "WA"),
+  ("West Virginia", "WV"),
+  ("Wisconsin", "WI"),
+  ("Wyoming", "WY"),
+  ("District of Columbia", "DC"),
+  ("American Samoa", "AS"),
+  ("Guam", "GU"),
+  ("Northern Mariana Islands", "MP"),
+  ("Puerto Rico", "PR"),
+  ("U.S. Virgin Islands", "VI"),
+];
+
+/// Two-letter code of a state or territory, given its full name
+/// (e.g. "California" -> "CA"), ignoring case
+pub fn state_code(name: &str) -> Option<&'static str> {
+  let name = name.trim();
+  STATES
+    .iter()
+    .find(|(n, _)| n.eq_ignore_ascii_case(name))
+    .map(|(_, code)| *code)
+}
+
+#[test]
+fn state_codes() {
+  for (name, code) in [
+    ("California", Some("CA")),
+    ("new york", Some("NY")),
+    ("WEST VIRGINIA", Some("WV")),
+    ("District of Columbia", Some("DC")),
+    ("Puerto Rico", Some("PR")),
+    (" Texas ", Some("TX")),
+    ("Narnia", None),
+    ("CA", None),
+  ] {
+    assert_eq!(state_code(name), code, "{}", name);
+  }
+}
+
+/// Find the City matching `name` and `state_id`, ignoring case
+pub fn find_city<'a>(cities: &'a [City], name: &str, state_id: &str) -> Option<&'a City> {
+  cities
+    .iter()
+    .find(|c| c.city.eq_ignore_ascii_case(name) && c.state_id.eq_ignore_ascii_case(state_id))
+}
+
+/// Cities in the state with code `state_id`, ignoring case
+pub fn cities_in_state<'a>(cities: &'a [City], state_id: &str) -> Vec<&'a City> {
+  cities
+    .iter()
+    .filter(|c| c.state_id.eq_ignore_ascii_case(state_id))
+    .collect()
+}
+
+#[test]
+fn cities_in_state_filter() {
+  let data = r#""city","state_id","lat","lng"
+"New York","NY","40.6943","-73.9249"
+"Los Angeles","CA","34.1139","-118.4068"
+"Buffalo","NY","42.9017","-78.8487"
+"#;
+  let cities = City::from_csv_reader(data.as_bytes()).unwrap();
+  let ny: Vec<&str> = cities_in_state(&cities, "ny")
+    .iter()
+    .map(|c| c.city.as_str())
+    .collect();
+  assert_eq!(ny, ["New York", "Buffalo"]);
+  assert!(cities_in_state(&cities, "TX").is_empty());
+}
+
+/// Find the City closest to a Point, or None if `cities` is empty
+pub fn nearest_city<'a>(cities: &'a [City], point: &Point) -> Option<&'a City> {
+  cities.iter().min_by(|a, b| {
+    let a = point.earth_distance_from(&Point::from(*a));
+    let b = point.earth_distance_from(&Point::from(*b));
+    a.total_cmp(&b)
+  })
+}
+
+#[test]
+fn nearest_city_of_three() {
+  let city = |city: &str, state_id: &str, lat, lng| City {
+    city: city.to_string(),
+    state_id: state_id.to_string(),
+    lat,
+    lng,
+  };
+  let cities = [
+    city("New York", "NY", 40.6943, -73.9249),
+    city("Chicago", "IL", 41.8373, -87.6862),
+    city("Los Angeles", "CA", 34.1139, -118.4068),
+  ];
+  let denver = Point::new(39.7621, -104.8759);
+  assert_eq!(nearest_city(&cities, &denver).unwrap().city, "Los Angeles");
+  let boston = Point::new(42.3188, -71.0846);
+  assert_eq!(nearest_city(&cities, &boston).unwrap().city, "New York");
+  assert!(nearest_city(&[], &boston).is_none());
+}
+
 /// Result of a GET /point request
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PointInfo {
   id: String,
   pub properties: PointProps,
 }
 
+impl PointInfo {
+  /// Canonical URL of the point as returned by NWS, e.g.
+  /// "https://api.weather.gov/points/40.7128,-74.006"
+  pub fn resource_url(&self) -> &str {
+    &self.id
+  }
+}
+
+#[test]
+fn point_resource_url() {
+  let pnt: PointInfo = serde_json::from_str(TEST_POINT).unwrap();
+  assert_eq!(
+    pnt.resource_url(),
+    "https://api.weather.gov/points/40.7128,-74.006"
+  );
+}
+
 /// Inner properties object of PointInfo
-#[derive(Serialize, Deserialize, Debug)]
+///
+/// The URL and ID fields are required, since an empty one would only
+/// fail later as a confusing request. time_zone defaults to empty when
+/// missing.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PointProps {
   #[serde(rename(deserialize = "forecastOffice"))]
   pub forecast_office: String,
@@ -110,161 +938,3150 @@ pub struct PointProps {
   pub county: String,
   #[serde(rename(deserialize = "fireWeatherZone"))]
   pub fire_weather_zone: String,
-  #[serde(rename(deserialize = "timeZone"))]
+  #[serde(rename(deserialize = "timeZone"), default)]
   pub time_zone: String,
   #[serde(rename(deserialize = "radarStation"))]
   pub radar_station: String,
 }
 
+impl PointProps {
+  /// Parse the WFO gridpoint out of the forecast URL
+  /// (.../gridpoints/{office}/{x},{y}/forecast)
+  pub fn grid_point(&self) -> Option<GridPoint> {
+    let path = self.forecast.split("/gridpoints/").nth(1)?;
+    let mut parts = path.split('/');
+    let office = parts.next()?;
+    let (x, y) = parts.next()?.split_once(',')?;
+    Some(GridPoint {
+      office: office.to_string(),
+      x: x.parse().ok()?,
+      y: y.parse().ok()?,
+    })
+  }
+}
+
+/// WFO gridpoint, identified by forecast office and grid coordinates
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GridPoint {
+  pub office: String,
+  pub x: u32,
+  pub y: u32,
+}
+
+#[test]
+fn grid_point() {
+  let pnt: PointInfo = serde_json::from_str(TEST_POINT).unwrap();
+  assert_eq!(
+    pnt.properties.grid_point(),
+    Some(GridPoint {
+      office: "OKX".to_string(),
+      x: 33,
+      y: 35
+    })
+  );
+}
+
+#[test]
+fn point_missing_fields() {
+  let json = TEST_POINT.replace(r#""timeZone": "America/New_York","#, "");
+  let pnt: PointInfo = serde_json::from_str(&json).unwrap();
+  assert_eq!(pnt.properties.time_zone, "");
+  assert_eq!(pnt.properties.radar_station, "KOKX");
+  // a renamed URL field is an error rather than an empty URL
+  let json = TEST_POINT.replace(r#""radarStation": "KOKX""#, r#""radar": "KOKX""#);
+  assert!(serde_json::from_str::<PointInfo>(&json).is_err());
+}
+
+#[cfg(test)]
+const TEST_POINT: &str = r#"{
+  "id": "https://api.weather.gov/points/40.7128,-74.006",
+  "type": "Feature",
+  "geometry": {"type": "Point", "coordinates": [-74.006, 40.7128]},
+  "properties": {
+    "@id": "https://api.weather.gov/points/40.7128,-74.006",
+    "cwa": "OKX",
+    "forecastOffice": "https://api.weather.gov/offices/OKX",
+    "gridId": "OKX",
+    "gridX": 33,
+    "gridY": 35,
+    "forecast": "https://api.weather.gov/gridpoints/OKX/33,35/forecast",
+    "forecastHourly": "https://api.weather.gov/gridpoints/OKX/33,35/forecast/hourly",
+    "forecastGridData": "https://api.weather.gov/gridpoints/OKX/33,35",
+    "observationStations": "https://api.weather.gov/gridpoints/OKX/33,35/stations",
+    "relativeLocation": {
+      "type": "Feature",
+      "geometry": {"type": "Point", "coordinates": [-74.0279259, 40.745251]},
+      "properties": {
+        "city": "Hoboken",
+        "state": "NJ",
+        "distance": {"unitCode": "wmoUnit:m", "value": 3932.3948},
+        "bearing": {"unitCode": "wmoUnit:degree_(angle)", "value": 156}
+      }
+    },
+    "forecastZone": "https://api.weather.gov/zones/forecast/NYZ072",
+    "county": "https://api.weather.gov/zones/county/NYC061",
+    "fireWeatherZone": "https://api.weather.gov/zones/fire/NYZ212",
+    "timeZone": "America/New_York",
+    "radarStation": "KOKX"
+  }
+}"#;
+
 /// inner relative_location object of PointProps
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RelativeLocation {
   pub geometry: Value,
   pub properties: RelativeProps,
 }
 
 /// inner properties object of RelativeLocation
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RelativeProps {
   pub city: String,
   pub state: String,
-  pub distance: Value,
-  pub bearing: Value,
+  /// Distance from the point in meters
+  #[serde(deserialize_with = "de_required_unit_value")]
+  pub distance: f64,
+  /// Bearing from the point in degrees
+  #[serde(deserialize_with = "de_required_unit_value")]
+  pub bearing: f64,
 }
 
-/// Result of GET /forecast
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Forecast {
-  pub properties: ForecastProps,
+impl RelativeLocation {
+  /// Point of the GeoJSON geometry. Note that GeoJSON coordinates
+  /// are in (lng, lat) order.
+  pub fn point(&self) -> Option<Point> {
+    let geometry: PointGeometry = serde_json::from_value(self.geometry.clone()).ok()?;
+    Some(geometry.into())
+  }
 }
 
-/// Inner properties object of Forecast
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ForecastProps {
-  pub updated: DateTime<Local>,
-  pub units: String,
-  #[serde(rename(deserialize = "generatedAt"))]
-  pub generated_at: DateTime<Local>,
-  pub elevation: Value,
-  pub periods: Vec<ForecastPeriod>,
+#[test]
+fn relative_location_point() {
+  let pnt: PointInfo = serde_json::from_str(TEST_POINT).unwrap();
+  let point = pnt.properties.relative_location.point().unwrap();
+  assert!((point.lat - 40.745_25).abs() < 1e-5);
+  assert!((point.lng - -74.027_93).abs() < 1e-5);
+  let loc = RelativeLocation {
+    geometry: Value::Null,
+    ..pnt.properties.relative_location
+  };
+  assert_eq!(loc.point(), None);
 }
 
-/// Single instance of item in periods object of ForecastProps
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ForecastPeriod {
-  pub number: u16,
-  pub name: String,
-  #[serde(rename(deserialize = "startTime"))]
-  pub start_time: DateTime<Local>,
-  #[serde(rename(deserialize = "endTime"))]
-  pub end_time: DateTime<Local>,
-  #[serde(rename(deserialize = "isDaytime"))]
-  pub is_day_time: bool,
-  pub temperature: i8,
-  #[serde(rename(deserialize = "temperatureUnit"))]
-  pub temperature_unit: String,
-  #[serde(rename(deserialize = "windSpeed"))]
-  pub wind_speed: Option<String>,
-  #[serde(rename(deserialize = "windDirection"))]
-  pub wind_direction: Option<String>,
-  pub icon: String,
-  #[serde(rename(deserialize = "shortForecast"))]
-  pub short_forecast: String,
-  #[serde(rename(deserialize = "detailedForecast"))]
-  pub detailed_forecast: String,
+impl RelativeProps {
+  /// Distance from the point in kilometers
+  pub fn distance_km(&self) -> f64 {
+    self.distance / 1000.0
+  }
 }
 
-/// Forecast output representation
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ForecastBundle {
-  pub start: DateTime<Local>,
-  pub end: DateTime<Local>,
-  pub temperature: i8,
-  pub wind_speed: String, // TODO parse from string to int "30 mph" -> 30
-  pub wind_direction: String,
-  pub short_forecast: String,
+#[test]
+fn relative_location() {
+  let loc: RelativeLocation = serde_json::from_str(
+    r#"{
+      "type": "Feature",
+      "geometry": {"type": "Point", "coordinates": [-74.0279259, 40.745251]},
+      "properties": {
+        "city": "Hoboken",
+        "state": "NJ",
+        "distance": {"unitCode": "wmoUnit:m", "value": 3932.3948},
+        "bearing": {"unitCode": "wmoUnit:degree_(angle)", "value": 156}
+      }
+    }"#,
+  )
+  .unwrap();
+  assert_eq!(loc.properties.city, "Hoboken");
+  assert_eq!(loc.properties.distance, 3932.3948);
+  assert!((loc.properties.distance_km() - 3.9323948).abs() < 1e-9);
+  assert_eq!(loc.properties.bearing, 156.0);
 }
 
-/// WeatherForecast output representation tied to a specific City.
+/// Result of GET /forecast
 ///
-/// This struct is passed directly into an embedded Database
+/// Timestamps are converted to the Local timezone by default. Use
+/// `Forecast<FixedOffset>` to keep the offset NWS reports for the
+/// forecast location instead.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct WeatherBundle {
-  pub location: City,
-  pub forecast: Vec<ForecastBundle>,
-  pub updated: DateTime<Local>,
+#[serde(bound(
+  serialize = "DateTime<Tz>: Serialize",
+  deserialize = "DateTime<Tz>: Deserialize<'de>"
+))]
+pub struct Forecast<Tz: TimeZone = Local> {
+  pub properties: ForecastProps<Tz>,
 }
 
-impl WeatherBundle {
-  /// Create a new WeatherBundle from a City and Forecast
+impl<Tz: TimeZone> Forecast<Tz> {
+  /// Iterate over the periods of the forecast
+  pub fn iter(&self) -> std::slice::Iter<'_, ForecastPeriod<Tz>> {
+    self.properties.periods.iter()
+  }
+
+  /// Periods overlapping the window from `start` to `end`
+  pub fn periods_in_range(
+    &self,
+    start: DateTime<Tz>,
+    end: DateTime<Tz>,
+  ) -> Vec<&ForecastPeriod<Tz>> {
+    self
+      .properties
+      .periods
+      .iter()
+      .filter(|p| p.start_time < end && p.end_time > start)
+      .collect()
+  }
+
+  /// Whether this forecast was generated after `other`, e.g. to skip
+  /// unchanged updates from forecast_stream
+  pub fn is_newer_than(&self, other: &Forecast<Tz>) -> bool {
+    self.properties.generated_at > other.properties.generated_at
+  }
+
+  /// The period in effect at `now`. If `now` falls in a gap between
+  /// periods (or before the first one), this is the next upcoming
+  /// period instead. None if every period has ended.
+  pub fn current(&self, now: DateTime<Tz>) -> Option<&ForecastPeriod<Tz>> {
+    let periods = &self.properties.periods;
+    periods
+      .iter()
+      .find(|p| p.start_time <= now && now < p.end_time)
+      .or_else(|| periods.iter().find(|p| p.start_time > now))
+  }
+
+  /// The period after Forecast::current
+  pub fn next(&self, now: DateTime<Tz>) -> Option<&ForecastPeriod<Tz>> {
+    let current = self.current(now)?;
+    self
+      .properties
+      .periods
+      .iter()
+      .find(|p| p.start_time >= current.end_time)
+  }
+
+  /// The (coldest, warmest) periods, compared in Fahrenheit so
+  /// periods in different temperature units rank correctly. None if
+  /// there are no periods.
+  pub fn extremes(&self) -> Option<(&ForecastPeriod<Tz>, &ForecastPeriod<Tz>)> {
+    let periods = &self.properties.periods;
+    let by_temperature = |a: &&ForecastPeriod<Tz>, b: &&ForecastPeriod<Tz>| {
+      a.temperature_fahrenheit()
+        .total_cmp(&b.temperature_fahrenheit())
+    };
+    Some((
+      periods.iter().min_by(by_temperature)?,
+      periods.iter().max_by(by_temperature)?,
+    ))
+  }
+
+  /// Summarize the periods of each calendar day, by the date of their
+  /// start_time in the forecast's timezone. Intended for hourly
+  /// forecasts.
+  pub fn daily_summaries(&self) -> Vec<DaySummary> {
+    let mut days: Vec<(NaiveDate, Vec<&ForecastPeriod<Tz>>)> = Vec::new();
+    for p in &self.properties.periods {
+      let date = p.start_time.date_naive();
+      match days.last_mut() {
+        Some((d, periods)) if *d == date => periods.push(p),
+        _ => days.push((date, vec![p])),
+      }
+    }
+    days
+      .into_iter()
+      .map(|(date, periods)| {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for p in &periods {
+          match counts.iter_mut().find(|(f, _)| *f == p.short_forecast) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((&p.short_forecast, 1)),
+          }
+        }
+        // max_by_key returns the last maximum, so reverse to prefer
+        // the earliest forecast on ties
+        let short_forecast = counts
+          .iter()
+          .rev()
+          .max_by_key(|(_, n)| *n)
+          .map(|(f, _)| f.to_string())
+          .unwrap_or_default();
+        DaySummary {
+          date,
+          high: periods
+            .iter()
+            .map(|p| p.temperature)
+            .max()
+            .unwrap_or_default(),
+          low: periods
+            .iter()
+            .map(|p| p.temperature)
+            .min()
+            .unwrap_or_default(),
+          short_forecast,
+          max_probability_of_precipitation: periods
+            .iter()
+            .filter_map(|p| p.probability_of_precipitation)
+            .max(),
+        }
+      })
+      .collect()
+  }
+}
+
+impl<'a, Tz: TimeZone> IntoIterator for &'a Forecast<Tz> {
+  type Item = &'a ForecastPeriod<Tz>;
+  type IntoIter = std::slice::Iter<'a, ForecastPeriod<Tz>>;
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+#[test]
+fn forecast_into_iter() {
+  let forecast = test_hourly_forecast(3);
+  assert_eq!(forecast.iter().count(), 3);
+  let mut numbers = Vec::new();
+  for p in &forecast {
+    numbers.push(p.number);
+  }
+  assert_eq!(numbers, [1, 2, 3]);
+  assert_eq!((&test_forecast(vec![])).into_iter().count(), 0);
+}
+
+/// Summary of a calendar day of forecast periods, from
+/// Forecast::daily_summaries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaySummary {
+  pub date: NaiveDate,
+  /// Highest temperature, in the forecast's temperature_unit
+  pub high: i8,
+  /// Lowest temperature, in the forecast's temperature_unit
+  pub low: i8,
+  /// Most common short_forecast of the day
+  pub short_forecast: String,
+  pub max_probability_of_precipitation: Option<u8>,
+}
+
+/// A daily forecast with hourly detail, from merge_forecasts
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(
+  serialize = "DateTime<Tz>: Serialize",
+  deserialize = "DateTime<Tz>: Deserialize<'de>"
+))]
+pub struct CombinedForecast<Tz: TimeZone = Local> {
+  pub periods: Vec<CombinedPeriod<Tz>>,
+}
+
+/// A named period of a daily forecast and the hourly periods starting
+/// within it
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(
+  serialize = "DateTime<Tz>: Serialize",
+  deserialize = "DateTime<Tz>: Deserialize<'de>"
+))]
+pub struct CombinedPeriod<Tz: TimeZone = Local> {
+  pub period: ForecastPeriod<Tz>,
+  pub hourly: Vec<ForecastPeriod<Tz>>,
+}
+
+/// Group the periods of an hourly forecast under the period of a daily
+/// forecast whose start_time..end_time contains their start_time.
+/// Hourly periods outside every daily period are dropped.
+pub fn merge_forecasts<Tz: TimeZone>(
+  daily: Forecast<Tz>,
+  hourly: Forecast<Tz>,
+) -> CombinedForecast<Tz> {
+  let mut periods: Vec<CombinedPeriod<Tz>> = daily
+    .properties
+    .periods
+    .into_iter()
+    .map(|period| CombinedPeriod {
+      period,
+      hourly: Vec::new(),
+    })
+    .collect();
+  for h in hourly.properties.periods {
+    let parent = periods
+      .iter_mut()
+      .find(|c| c.period.start_time <= h.start_time && h.start_time < c.period.end_time);
+    if let Some(parent) = parent {
+      parent.hourly.push(h);
+    }
+  }
+  CombinedForecast { periods }
+}
+
+/// Inner properties object of Forecast
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(
+  serialize = "DateTime<Tz>: Serialize",
+  deserialize = "DateTime<Tz>: Deserialize<'de>"
+))]
+pub struct ForecastProps<Tz: TimeZone = Local> {
+  pub updated: DateTime<Tz>,
+  pub units: String,
+  #[serde(rename(deserialize = "generatedAt"))]
+  pub generated_at: DateTime<Tz>,
+  pub elevation: Measurement,
+  pub periods: Vec<ForecastPeriod<Tz>>,
+}
+
+impl<Tz: TimeZone> ForecastProps<Tz> {
+  /// Elevation of the forecast in meters, converted from feet if the
+  /// unitCode is wmoUnit:ft_i
+  pub fn elevation_meters(&self) -> Option<f64> {
+    self.elevation.as_meters()
+  }
+}
+
+#[test]
+fn forecast_fixed_offset() {
+  let json = test_forecast_json(1).replace("-04:00", "-07:00");
+  let forecast: Forecast<FixedOffset> = serde_json::from_str(&json).unwrap();
+  let period = &forecast.properties.periods[0];
+  assert_eq!(period.start_time.offset().local_minus_utc(), -7 * 3600);
+  assert_eq!(period.start_time.to_rfc3339(), "2021-08-21T18:00:00-07:00");
+  assert_eq!(period.temperature_fahrenheit(), 72.0);
+}
+
+#[test]
+fn elevation_meters() {
+  let mut props = test_forecast(vec![]).properties;
+  props.elevation = Measurement::new("wmoUnit:m", Some(2.1336));
+  assert_eq!(props.elevation_meters(), Some(2.1336));
+  props.elevation = Measurement::new("wmoUnit:ft_i", Some(100.0));
+  assert!((props.elevation_meters().unwrap() - 30.48).abs() < 1e-9);
+  props.elevation = Measurement::new("wmoUnit:m", None);
+  assert_eq!(props.elevation_meters(), None);
+}
+
+/// Single instance of item in periods object of ForecastProps
+///
+/// Descriptive fields default to empty when missing, so a renamed NWS
+/// field doesn't fail the whole forecast.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(
+  serialize = "DateTime<Tz>: Serialize",
+  deserialize = "DateTime<Tz>: Deserialize<'de>"
+))]
+pub struct ForecastPeriod<Tz: TimeZone = Local> {
+  pub number: u16,
+  #[serde(default)]
+  pub name: String,
+  #[serde(rename(deserialize = "startTime"))]
+  pub start_time: DateTime<Tz>,
+  #[serde(rename(deserialize = "endTime"))]
+  pub end_time: DateTime<Tz>,
+  #[serde(rename(deserialize = "isDaytime"))]
+  pub is_day_time: bool,
+  pub temperature: i8,
+  #[serde(
+    rename(deserialize = "temperatureUnit"),
+    deserialize_with = "de_temperature_unit"
+  )]
+  pub temperature_unit: String,
+  #[serde(rename(deserialize = "windSpeed"), default)]
+  pub wind_speed: Option<String>,
+  #[serde(rename(deserialize = "windDirection"), default)]
+  pub wind_direction: Option<String>,
+  #[serde(default)]
+  pub icon: String,
+  #[serde(rename(deserialize = "shortForecast"), default)]
+  pub short_forecast: String,
+  #[serde(rename(deserialize = "detailedForecast"), default)]
+  pub detailed_forecast: String,
+  #[serde(
+    rename(deserialize = "probabilityOfPrecipitation"),
+    deserialize_with = "de_unit_value",
+    default
+  )]
+  pub probability_of_precipitation: Option<u8>,
+  /// Dewpoint in degrees Celsius. NWS always reports this field with
+  /// a `wmoUnit:degC` unitCode, regardless of temperature_unit.
+  #[serde(
+    rename(deserialize = "dewpoint"),
+    deserialize_with = "de_unit_value",
+    default
+  )]
+  pub dewpoint_celsius: Option<f32>,
+  #[serde(
+    rename(deserialize = "relativeHumidity"),
+    deserialize_with = "de_unit_value",
+    default
+  )]
+  pub relative_humidity: Option<u8>,
+  /// Direction the temperature is heading during the period, e.g.
+  /// "falling into the 30s overnight"
+  #[serde(rename(deserialize = "temperatureTrend"), default)]
+  pub temperature_trend: Option<Trend>,
+}
+
+/// Trend of ForecastPeriod.temperature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trend {
+  Rising,
+  Falling,
+  /// Any other trend NWS may report
+  #[serde(other)]
+  Unknown,
+}
+
+#[test]
+fn temperature_trend() {
+  assert_eq!(test_period().temperature_trend, None);
+  let json = TEST_PERIOD.replace(
+    r#""temperatureTrend": null,"#,
+    r#""temperatureTrend": "falling","#,
+  );
+  let p: ForecastPeriod = serde_json::from_str(&json).unwrap();
+  assert_eq!(p.temperature_trend, Some(Trend::Falling));
+  let json = TEST_PERIOD.replace(r#""temperatureTrend": null,"#, "");
+  let p: ForecastPeriod = serde_json::from_str(&json).unwrap();
+  assert_eq!(p.temperature_trend, None);
+  let json = TEST_PERIOD.replace(
+    r#""temperatureTrend": null,"#,
+    r#""temperatureTrend": "steady","#,
+  );
+  let p: ForecastPeriod = serde_json::from_str(&json).unwrap();
+  assert_eq!(p.temperature_trend, Some(Trend::Unknown));
+}
+
+#[test]
+fn period_missing_fields() {
+  let json = TEST_PERIOD
+    .replace(r#""windDirection": "S","#, "")
+    .replace(r#""name": "Tonight","#, "");
+  let p: ForecastPeriod = serde_json::from_str(&json).unwrap();
+  assert_eq!(p.wind_direction, None);
+  assert_eq!(p.name, "");
+  assert_eq!(p.wind_speed.as_deref(), Some("10 mph"));
+}
+
+/// A {unitCode, value} object, e.g.
+/// {"unitCode": "wmoUnit:degC", "value": 21.7}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Measurement {
+  /// WMO unit code, e.g. "wmoUnit:degC"
+  #[serde(rename(deserialize = "unitCode"), alias = "unit_code", default)]
+  pub unit_code: String,
+  pub value: Option<f64>,
+}
+
+impl Measurement {
+  /// Create a new Measurement
+  pub fn new(unit_code: impl Into<String>, value: Option<f64>) -> Self {
+    Measurement {
+      unit_code: unit_code.into(),
+      value,
+    }
+  }
+
+  /// Unit code without the "wmoUnit:" prefix
+  fn unit(&self) -> &str {
+    self
+      .unit_code
+      .strip_prefix("wmoUnit:")
+      .unwrap_or(&self.unit_code)
+  }
+
+  /// The value in degrees Celsius, or None if it is null or not a
+  /// temperature
+  pub fn as_celsius(&self) -> Option<f64> {
+    let v = self.value?;
+    match self.unit() {
+      "degC" => Some(v),
+      "degF" => Some((v - 32.0) * 5.0 / 9.0),
+      "K" => Some(v - 273.15),
+      _ => None,
+    }
+  }
+
+  /// The value in meters, or None if it is null or not a length
+  pub fn as_meters(&self) -> Option<f64> {
+    let v = self.value?;
+    match self.unit() {
+      "m" => Some(v),
+      "km" => Some(v * 1000.0),
+      "ft_i" => Some(v * units::METERS_PER_FOOT),
+      "mi_i" => Some(v * units::KM_PER_MILE * 1000.0),
+      _ => None,
+    }
+  }
+
+  /// The value in km/h, or None if it is null or not a speed
+  pub fn as_kmh(&self) -> Option<f64> {
+    let v = self.value?;
+    match self.unit() {
+      "km_h-1" => Some(v),
+      "m_s-1" => Some(units::ms_to_kmh(v)),
+      "kn" => Some(units::knots_to_kmh(v)),
+      "mi_i_h-1" => Some(units::mph_to_kmh(v)),
+      _ => None,
+    }
+  }
+
+  /// The value in mph, or None if it is null or not a speed
+  pub fn as_mph(&self) -> Option<f64> {
+    self.as_kmh().map(units::kmh_to_mph)
+  }
+}
+
+#[test]
+fn measurement_conversions() {
+  let approx = |a: Option<f64>, b: f64| (a.unwrap() - b).abs() < 1e-9;
+  assert!(approx(
+    Measurement::new("wmoUnit:degC", Some(20.0)).as_celsius(),
+    20.0
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:degF", Some(212.0)).as_celsius(),
+    100.0
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:K", Some(273.15)).as_celsius(),
+    0.0
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:m", Some(5.0)).as_meters(),
+    5.0
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:km", Some(1.5)).as_meters(),
+    1500.0
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:ft_i", Some(10.0)).as_meters(),
+    3.048
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:mi_i", Some(1.0)).as_meters(),
+    1609.344
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:km_h-1", Some(10.0)).as_kmh(),
+    10.0
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:m_s-1", Some(10.0)).as_kmh(),
+    36.0
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:kn", Some(10.0)).as_kmh(),
+    18.52
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:mi_i_h-1", Some(10.0)).as_kmh(),
+    16.09344
+  ));
+  assert!(approx(
+    Measurement::new("wmoUnit:km_h-1", Some(16.09344)).as_mph(),
+    10.0
+  ));
+  assert_eq!(Measurement::new("wmoUnit:m", Some(5.0)).as_celsius(), None);
+  assert_eq!(Measurement::new("wmoUnit:degC", None).as_celsius(), None);
+  let m: Measurement =
+    serde_json::from_str(r#"{"unitCode": "wmoUnit:degC", "value": 21.7}"#).unwrap();
+  assert_eq!(m, Measurement::new("wmoUnit:degC", Some(21.7)));
+  let json = serde_json::to_string(&m).unwrap();
+  assert_eq!(serde_json::from_str::<Measurement>(&json).unwrap(), m);
+}
+
+/// Deserialize the inner value of a {unitCode, value} object, mapping
+/// JSON null to None
+fn de_unit_value<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+  D: Deserializer<'de>,
+  T: Deserialize<'de>,
+{
+  #[derive(Deserialize)]
+  struct UnitValue<T> {
+    value: Option<T>,
+  }
+  Ok(Option::<UnitValue<T>>::deserialize(deserializer)?.and_then(|v| v.value))
+}
+
+/// Same as de_unit_value, but JSON null is an error
+fn de_required_unit_value<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+  D: Deserializer<'de>,
+  T: Deserialize<'de>,
+{
+  de_unit_value(deserializer)?.ok_or_else(|| serde::de::Error::custom("missing unit value"))
+}
+
+/// Deserialize a temperature unit, warning if it is not "F" or "C"
+fn de_temperature_unit<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  let unit = String::deserialize(deserializer)?;
+  if unit != "F" && unit != "C" {
+    warn!("unrecognized temperature unit {:?}, assuming F", unit);
+  }
+  Ok(unit)
+}
+
+impl<Tz: TimeZone> ForecastPeriod<Tz> {
+  /// Parse wind_speed into a low value and an optional high value in
+  /// mph, ignoring the trailing unit text.
+  ///
+  /// "30 mph" -> (30, None), "10 to 20 mph" -> (10, Some(20))
+  pub fn wind_speed_mph(&self) -> Option<(u16, Option<u16>)> {
+    let mut words = self.wind_speed.as_deref()?.split_whitespace();
+    let low = words.next()?.parse().ok()?;
+    match (words.next(), words.next()) {
+      (Some("to"), Some(high)) => Some((low, Some(high.parse().ok()?))),
+      _ => Some((low, None)),
+    }
+  }
+
+  /// Whether temperature_unit is "C". An empty or unrecognized unit
+  /// is treated as "F", which is warned about when deserializing.
+  fn is_celsius(&self) -> bool {
+    self.temperature_unit == "C"
+  }
+
+  /// Temperature in degrees Celsius, converted from temperature_unit
+  pub fn temperature_celsius(&self) -> f32 {
+    let t = f32::from(self.temperature);
+    if self.is_celsius() {
+      t
+    } else {
+      (t - 32.0) * 5.0 / 9.0
+    }
+  }
+
+  /// Temperature in degrees Fahrenheit, converted from temperature_unit
+  pub fn temperature_fahrenheit(&self) -> f32 {
+    let t = f32::from(self.temperature);
+    if self.is_celsius() {
+      t * 9.0 / 5.0 + 32.0
+    } else {
+      t
+    }
+  }
+
+  /// Heat index in degrees Fahrenheit using the NWS Rothfusz
+  /// regression, or None below 80°F or when relative_humidity is
+  /// missing
+  pub fn heat_index_f(&self) -> Option<f32> {
+    let t = self.temperature_fahrenheit();
+    let rh = f32::from(self.relative_humidity?);
+    if t < 80.0 {
+      return None;
+    }
+    let mut hi = -42.379 + 2.049_015 * t + 10.143_331 * rh
+      - 0.224_755_4 * t * rh
+      - 0.006_837_83 * t * t
+      - 0.054_817_17 * rh * rh
+      + 0.001_228_74 * t * t * rh
+      + 0.000_852_82 * t * rh * rh
+      - 0.000_001_99 * t * t * rh * rh;
+    if rh < 13.0 && t <= 112.0 {
+      hi -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && t <= 87.0 {
+      hi += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+    }
+    Some(hi)
+  }
+
+  /// Wind chill in degrees Fahrenheit using the NWS formula, or None
+  /// above 50°F or below 3 mph of wind. The low end of a wind speed
+  /// range is used.
+  pub fn wind_chill_f(&self) -> Option<f32> {
+    let t = self.temperature_fahrenheit();
+    let v = f32::from(self.wind_speed_mph()?.0);
+    if t > 50.0 || v < 3.0 {
+      return None;
+    }
+    let v = v.powf(0.16);
+    Some(35.74 + 0.6215 * t - 35.75 * v + 0.4275 * t * v)
+  }
+
+  /// Path segments of the icon URL after /icons/{set}/, without the
+  /// query string, e.g. ["day", "rain,40", "tsra,70"]
+  fn icon_segments(&self) -> Vec<&str> {
+    let path = self.icon.split('?').next().unwrap_or_default();
+    match path.split_once("/icons/") {
+      Some((_, path)) => path.split('/').skip(1).collect(),
+      None => Vec::new(),
+    }
+  }
+
+  /// Parse the condition codes and optional precipitation
+  /// percentages from the icon URL
+  ///
+  /// ".../icons/land/day/rain,40/tsra,70?size=medium" ->
+  /// [("rain", Some(40)), ("tsra", Some(70))]
+  pub fn icon_conditions(&self) -> Vec<(String, Option<u8>)> {
+    self
+      .icon_segments()
+      .into_iter()
+      .skip(1)
+      .map(|s| match s.split_once(',') {
+        Some((code, pct)) => (code.to_string(), pct.parse().ok()),
+        None => (s.to_string(), None),
+      })
+      .collect()
+  }
+
+  /// Whether the icon URL is for daytime, or None if it has no
+  /// day/night segment
+  pub fn icon_is_day(&self) -> Option<bool> {
+    match self.icon_segments().first() {
+      Some(&"day") => Some(true),
+      Some(&"night") => Some(false),
+      _ => None,
+    }
+  }
+
+  /// Parse wind_direction into a CompassDirection
+  pub fn wind_compass(&self) -> Option<CompassDirection> {
+    self.wind_direction.as_deref()?.parse().ok()
+  }
+
+  /// Azimuth of wind_direction in degrees (N=0, NE=45, etc.), rounded
+  /// to the nearest degree
+  pub fn wind_direction_degrees(&self) -> Option<u16> {
+    Some(self.wind_compass()?.degrees().round() as u16)
+  }
+
+  /// Wind as (eastward, northward) components in mph, pointing
+  /// towards wind_direction, e.g. "N" at 10 mph is (0.0, 10.0). Note
+  /// that NWS reports the direction the wind blows from, so negate both
+  /// components for the direction of flow. A wind_speed range uses its
+  /// midpoint.
+  pub fn wind_vector_mph(&self) -> Option<(f32, f32)> {
+    let speed = match self.wind_speed_mph()? {
+      (low, Some(high)) => (f32::from(low) + f32::from(high)) / 2.0,
+      (low, None) => f32::from(low),
+    };
+    let azimuth = self.wind_compass()?.degrees().to_radians();
+    Some((speed * azimuth.sin(), speed * azimuth.cos()))
+  }
+
+  /// Length of the period, from start_time to end_time
+  pub fn duration(&self) -> Duration {
+    self.end_time.naive_utc() - self.start_time.naive_utc()
+  }
+
+  /// Whether this is a nighttime period longer than 8 hours, such as
+  /// "Tonight" in a 12-hour forecast
+  pub fn is_overnight(&self) -> bool {
+    !self.is_day_time && self.duration() > Duration::hours(8)
+  }
+}
+
+#[test]
+fn period_duration() {
+  let p = test_period();
+  assert_eq!(p.duration(), Duration::hours(12));
+  assert!(p.is_overnight());
+  let forecast = test_hourly_forecast(1);
+  let mut p = forecast.properties.periods.into_iter().next().unwrap();
+  assert_eq!(p.duration(), Duration::hours(1));
+  assert!(!p.is_overnight());
+  p.end_time = p.start_time + Duration::hours(12);
+  p.is_day_time = true;
+  assert!(!p.is_overnight());
+}
+
+#[test]
+fn feels_like() {
+  let mut p = test_period();
+  assert_eq!(p.heat_index_f(), None);
+  assert_eq!(p.wind_chill_f(), None);
+  // NWS heat index chart: 96°F at 65% humidity feels like 121°F
+  p.temperature = 96;
+  p.relative_humidity = Some(65);
+  assert_eq!(p.heat_index_f().unwrap().round(), 121.0);
+  p.relative_humidity = None;
+  assert_eq!(p.heat_index_f(), None);
+  // NWS wind chill chart: 0°F with 15 mph wind feels like -19°F
+  p.temperature = 0;
+  p.wind_speed = Some("15 mph".to_string());
+  assert_eq!(p.wind_chill_f().unwrap().round(), -19.0);
+  p.temperature = 40;
+  p.wind_speed = Some("10 to 20 mph".to_string());
+  assert_eq!(p.wind_chill_f().unwrap().round(), 34.0);
+  p.wind_speed = Some("2 mph".to_string());
+  assert_eq!(p.wind_chill_f(), None);
+}
+
+#[test]
+fn icon_single_condition() {
+  let p = test_period();
+  assert_eq!(p.icon_conditions(), [("tsra_hi".to_string(), Some(40))]);
+  assert_eq!(p.icon_is_day(), Some(false));
+}
+
+#[test]
+fn icon_dual_condition() {
+  let mut p = test_period();
+  p.icon = "https://api.weather.gov/icons/land/day/rain,40/tsra,70?size=medium".to_string();
+  assert_eq!(
+    p.icon_conditions(),
+    [
+      ("rain".to_string(), Some(40)),
+      ("tsra".to_string(), Some(70))
+    ]
+  );
+  assert_eq!(p.icon_is_day(), Some(true));
+}
+
+#[test]
+fn icon_no_percentage() {
+  let mut p = test_period();
+  p.icon = "https://api.weather.gov/icons/land/day/few?size=medium".to_string();
+  assert_eq!(p.icon_conditions(), [("few".to_string(), None)]);
+  p.icon = "not a url".to_string();
+  assert!(p.icon_conditions().is_empty());
+  assert_eq!(p.icon_is_day(), None);
+}
+
+/// The 16 points of the compass, as used in wind_direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompassDirection {
+  N,
+  NNE,
+  NE,
+  ENE,
+  E,
+  ESE,
+  SE,
+  SSE,
+  S,
+  SSW,
+  SW,
+  WSW,
+  W,
+  WNW,
+  NW,
+  NNW,
+}
+
+impl CompassDirection {
+  /// All 16 directions clockwise from N
+  pub const ALL: [CompassDirection; 16] = [
+    CompassDirection::N,
+    CompassDirection::NNE,
+    CompassDirection::NE,
+    CompassDirection::ENE,
+    CompassDirection::E,
+    CompassDirection::ESE,
+    CompassDirection::SE,
+    CompassDirection::SSE,
+    CompassDirection::S,
+    CompassDirection::SSW,
+    CompassDirection::SW,
+    CompassDirection::WSW,
+    CompassDirection::W,
+    CompassDirection::WNW,
+    CompassDirection::NW,
+    CompassDirection::NNW,
+  ];
+
+  /// Azimuth in degrees clockwise from north
+  pub fn degrees(&self) -> f32 {
+    *self as u8 as f32 * 22.5
+  }
+}
+
+impl std::str::FromStr for CompassDirection {
+  type Err = Error;
+  fn from_str(s: &str) -> Result<Self, Error> {
+    CompassDirection::ALL
+      .into_iter()
+      .find(|d| format!("{:?}", d).eq_ignore_ascii_case(s.trim()))
+      .ok_or_else(|| Error::InvalidDirection(s.to_string()))
+  }
+}
+
+#[test]
+fn wind_direction_degrees() {
+  let expected = [
+    ("N", 0),
+    ("NNE", 23),
+    ("NE", 45),
+    ("ENE", 68),
+    ("E", 90),
+    ("ESE", 113),
+    ("SE", 135),
+    ("SSE", 158),
+    ("S", 180),
+    ("SSW", 203),
+    ("SW", 225),
+    ("WSW", 248),
+    ("W", 270),
+    ("WNW", 293),
+    ("NW", 315),
+    ("NNW", 338),
+  ];
+  let mut p = test_period();
+  for (dir, degrees) in expected {
+    p.wind_direction = Some(dir.to_string());
+    assert_eq!(p.wind_direction_degrees(), Some(degrees), "{}", dir);
+  }
+  p.wind_direction = None;
+  assert_eq!(p.wind_direction_degrees(), None);
+  p.wind_direction = Some("up".to_string());
+  assert_eq!(p.wind_direction_degrees(), None);
+}
+
+#[test]
+fn wind_vector_mph() {
+  let approx =
+    |(x, y): (f32, f32), (ex, ey): (f32, f32)| (x - ex).abs() < 1e-4 && (y - ey).abs() < 1e-4;
+  let mut p = test_period();
+  p.wind_direction = Some("N".to_string());
+  assert!(approx(p.wind_vector_mph().unwrap(), (0.0, 10.0)));
+  p.wind_direction = Some("E".to_string());
+  p.wind_speed = Some("10 to 20 mph".to_string());
+  assert!(approx(p.wind_vector_mph().unwrap(), (15.0, 0.0)));
+  p.wind_direction = Some("SW".to_string());
+  p.wind_speed = Some("2 mph".to_string());
+  let half = 2.0_f32.sqrt();
+  assert!(approx(p.wind_vector_mph().unwrap(), (-half, -half)));
+  p.wind_direction = None;
+  assert_eq!(p.wind_vector_mph(), None);
+}
+
+#[cfg(test)]
+const TEST_PERIOD: &str = r#"{
+  "number": 1,
+  "name": "Tonight",
+  "startTime": "2021-08-21T18:00:00-04:00",
+  "endTime": "2021-08-22T06:00:00-04:00",
+  "isDaytime": false,
+  "temperature": 72,
+  "temperatureUnit": "F",
+  "temperatureTrend": null,
+  "windSpeed": "10 mph",
+  "windDirection": "S",
+  "icon": "https://api.weather.gov/icons/land/night/tsra_hi,40?size=medium",
+  "shortForecast": "Chance Showers And Thunderstorms",
+  "detailedForecast": "A chance of showers and thunderstorms. Mostly cloudy, with a low around 72.",
+  "probabilityOfPrecipitation": {"unitCode": "wmoUnit:percent", "value": null},
+  "dewpoint": {"unitCode": "wmoUnit:degC", "value": 21.666666666666668},
+  "relativeHumidity": {"unitCode": "wmoUnit:percent", "value": 82}
+}"#;
+
+/// JSON of a GET /forecast response with `n` copies of TEST_PERIOD
+#[cfg(test)]
+fn test_forecast_json(n: usize) -> String {
+  format!(
+    r#"{{
+      "properties": {{
+        "updated": "2021-08-21T19:38:19+00:00",
+        "units": "us",
+        "generatedAt": "2021-08-21T20:14:12+00:00",
+        "elevation": {{"unitCode": "wmoUnit:m", "value": 2.1336}},
+        "periods": [{}]
+      }}
+    }}"#,
+    vec![TEST_PERIOD; n].join(",")
+  )
+}
+
+#[cfg(test)]
+fn test_period() -> ForecastPeriod {
+  serde_json::from_str(TEST_PERIOD).unwrap()
+}
+
+#[test]
+fn probability_of_precipitation() {
+  assert_eq!(test_period().probability_of_precipitation, None);
+  let p: ForecastPeriod =
+    serde_json::from_str(&TEST_PERIOD.replace(r#""value": null"#, r#""value": 40"#)).unwrap();
+  assert_eq!(p.probability_of_precipitation, Some(40));
+}
+
+#[test]
+fn dewpoint_and_humidity() {
+  let p = test_period();
+  assert!((p.dewpoint_celsius.unwrap() - 21.666_666).abs() < 1e-4);
+  assert_eq!(p.relative_humidity, Some(82));
+  let p: ForecastPeriod = serde_json::from_str(
+    &TEST_PERIOD
+      .replace(r#""value": 21.666666666666668"#, r#""value": null"#)
+      .replace(r#""value": 82"#, r#""value": null"#),
+  )
+  .unwrap();
+  assert_eq!(p.dewpoint_celsius, None);
+  assert_eq!(p.relative_humidity, None);
+}
+
+#[test]
+fn wind_speed_single() {
+  let mut p = test_period();
+  p.wind_speed = Some("30 mph".to_string());
+  assert_eq!(p.wind_speed_mph(), Some((30, None)));
+}
+
+#[test]
+fn wind_speed_range() {
+  let mut p = test_period();
+  p.wind_speed = Some("10 to 20 mph".to_string());
+  assert_eq!(p.wind_speed_mph(), Some((10, Some(20))));
+}
+
+#[test]
+fn wind_speed_garbage() {
+  let mut p = test_period();
+  p.wind_speed = Some("breezy".to_string());
+  assert_eq!(p.wind_speed_mph(), None);
+  p.wind_speed = Some("10 to twenty mph".to_string());
+  assert_eq!(p.wind_speed_mph(), None);
+  p.wind_speed = None;
+  assert_eq!(p.wind_speed_mph(), None);
+}
+#[test]
+fn fahrenheit_to_celsius() {
+  let mut p = test_period();
+  p.temperature = 50;
+  p.temperature_unit = "F".to_string();
+  assert_eq!(p.temperature_celsius(), 10.0);
+  assert_eq!(p.temperature_fahrenheit(), 50.0);
+  p.temperature = i8::MIN;
+  assert!((p.temperature_celsius() - -88.888_89).abs() < 1e-3);
+}
+
+#[test]
+fn celsius_to_fahrenheit() {
+  let mut p = test_period();
+  p.temperature = -40;
+  p.temperature_unit = "C".to_string();
+  assert_eq!(p.temperature_fahrenheit(), -40.0);
+  assert_eq!(p.temperature_celsius(), -40.0);
+  p.temperature = i8::MAX;
+  assert!((p.temperature_fahrenheit() - 260.6).abs() < 1e-3);
+}
+
+#[test]
+fn empty_temperature_unit() {
+  let mut p = test_period();
+  p.temperature = 50;
+  for unit in ["", "K"] {
+    p.temperature_unit = unit.to_string();
+    assert_eq!(p.temperature_fahrenheit(), 50.0);
+    assert_eq!(p.temperature_celsius(), 10.0);
+  }
+}
+
+/// Forecast output representation
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ForecastBundle {
+  pub start: DateTime<Local>,
+  pub end: DateTime<Local>,
+  pub temperature: i8,
+  /// Unit of temperature, "F" or "C". Bundles saved before this field
+  /// was added load as "F".
+  #[serde(default = "default_temperature_unit")]
+  pub temperature_unit: String,
+  /// e.g. "10 to 20 mph", see ForecastPeriod::wind_speed_mph
+  pub wind_speed: Option<String>,
+  pub wind_direction: Option<String>,
+  pub short_forecast: String,
+  /// Name of the period, e.g. "Tonight". Bundles saved before this
+  /// field was added load as None.
+  #[serde(default)]
+  pub name: Option<String>,
+  #[serde(default)]
+  pub detailed_forecast: Option<String>,
+}
+
+impl From<&ForecastPeriod> for ForecastBundle {
+  fn from(i: &ForecastPeriod) -> Self {
+    ForecastBundle {
+      start: i.start_time,
+      end: i.end_time,
+      temperature: i.temperature,
+      temperature_unit: i.temperature_unit.clone(),
+      wind_speed: i.wind_speed.clone(),
+      wind_direction: i.wind_direction.clone(),
+      short_forecast: i.short_forecast.to_string(),
+      name: Some(i.name.clone()),
+      detailed_forecast: Some(i.detailed_forecast.clone()),
+    }
+  }
+}
+
+fn default_temperature_unit() -> String {
+  "F".to_string()
+}
+
+/// Write ", Wind {direction} {speed}" if either is known
+fn fmt_wind(
+  f: &mut fmt::Formatter,
+  speed: &Option<String>,
+  direction: &Option<String>,
+) -> fmt::Result {
+  match (direction.as_deref(), speed.as_deref()) {
+    (Some(d), Some(s)) => write!(f, ", Wind {} {}", d, s),
+    (Some(x), None) | (None, Some(x)) => write!(f, ", Wind {}", x),
+    (None, None) => Ok(()),
+  }
+}
+
+/// e.g. "Tonight: 52°F, Wind NW 10 mph — Partly Cloudy"
+impl<Tz: TimeZone> fmt::Display for ForecastPeriod<Tz> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{}: {}°{}",
+      self.name, self.temperature, self.temperature_unit
+    )?;
+    fmt_wind(f, &self.wind_speed, &self.wind_direction)?;
+    write!(f, " — {}", self.short_forecast)
+  }
+}
+
+/// e.g. "18:00-19:00: 52°F, Wind NW 10 mph — Partly Cloudy"
+impl fmt::Display for ForecastBundle {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{}-{}: {}°{}",
+      self.start.format("%H:%M"),
+      self.end.format("%H:%M"),
+      self.temperature,
+      self.temperature_unit
+    )?;
+    fmt_wind(f, &self.wind_speed, &self.wind_direction)?;
+    write!(f, " — {}", self.short_forecast)
+  }
+}
+
+#[test]
+fn display_forecast() {
+  let period = test_period();
+  assert_eq!(
+    period.to_string(),
+    "Tonight: 72°F, Wind S 10 mph — Chance Showers And Thunderstorms"
+  );
+  let mut bundle = ForecastBundle::from(&period);
+  let start = bundle.start.format("%H:%M").to_string();
+  let end = bundle.end.format("%H:%M").to_string();
+  assert_eq!(
+    bundle.to_string(),
+    format!(
+      "{}-{}: 72°F, Wind S 10 mph — Chance Showers And Thunderstorms",
+      start, end
+    )
+  );
+  bundle.wind_speed = None;
+  bundle.wind_direction = None;
+  assert_eq!(
+    bundle.to_string(),
+    format!("{}-{}: 72°F — Chance Showers And Thunderstorms", start, end)
+  );
+  bundle.temperature = 22;
+  bundle.temperature_unit = "C".to_string();
+  assert_eq!(
+    bundle.to_string(),
+    format!("{}-{}: 22°C — Chance Showers And Thunderstorms", start, end)
+  );
+}
+
+/// WeatherForecast output representation tied to a specific City.
+///
+/// This struct is passed directly into an embedded Database
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WeatherBundle {
+  pub location: City,
+  pub forecast: Vec<ForecastBundle>,
+  pub updated: DateTime<Local>,
+}
+
+impl WeatherBundle {
+  /// Create a new WeatherBundle from a City and Forecast
   pub fn new(loc: City, fcb: Forecast) -> Self {
-    let mut vec = Vec::new();
-    for i in fcb.properties.periods.iter() {
-      let i = ForecastBundle {
-        start: i.start_time,
-        end: i.end_time,
-        temperature: i.temperature,
-        wind_speed: i.wind_speed.as_ref().unwrap().to_string(),
-        wind_direction: i.wind_direction.as_ref().unwrap().to_string(),
-        short_forecast: i.short_forecast.to_string(),
+    WeatherBundle {
+      location: loc,
+      forecast: fcb
+        .properties
+        .periods
+        .iter()
+        .map(ForecastBundle::from)
+        .collect(),
+      updated: fcb.properties.updated,
+    }
+  }
+
+  /// Write the WeatherBundle to a JSON file
+  pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, self)?;
+    writer.flush()?;
+    Ok(())
+  }
+
+  /// Read a WeatherBundle from a JSON file written by save_json
+  pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+    let file = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(file)?)
+  }
+}
+
+/// GeoJSON FeatureCollection with a Point feature per WeatherBundle,
+/// located at its City. Each feature's properties are the city, state
+/// and the temperature and short forecast of its first period.
+pub fn to_geojson(bundles: &[WeatherBundle]) -> Value {
+  let features: Vec<Value> = bundles
+    .iter()
+    .map(|b| {
+      let current = b.forecast.first();
+      serde_json::json!({
+        "type": "Feature",
+        "geometry": {
+          "type": "Point",
+          "coordinates": [b.location.lng, b.location.lat],
+        },
+        "properties": {
+          "city": b.location.city,
+          "state": b.location.state_id,
+          "temperature": current.map(|f| f.temperature),
+          "shortForecast": current.map(|f| f.short_forecast.as_str()),
+          "updated": b.updated,
+        },
+      })
+    })
+    .collect();
+  serde_json::json!({
+    "type": "FeatureCollection",
+    "features": features,
+  })
+}
+
+#[cfg(test)]
+fn test_forecast(periods: Vec<ForecastPeriod>) -> Forecast {
+  let updated = test_period().start_time;
+  Forecast {
+    properties: ForecastProps {
+      updated,
+      units: "us".to_string(),
+      generated_at: updated,
+      elevation: Measurement::new("wmoUnit:m", Some(2.1336)),
+      periods,
+    },
+  }
+}
+
+/// Build a forecast of consecutive hourly periods starting at the
+/// start_time of test_period
+#[cfg(test)]
+fn test_hourly_forecast(hours: i64) -> Forecast {
+  let start = test_period().start_time;
+  let periods = (0..hours)
+    .map(|i| {
+      let mut p = test_period();
+      p.number = i as u16 + 1;
+      p.start_time = start + chrono::Duration::hours(i);
+      p.end_time = start + chrono::Duration::hours(i + 1);
+      p
+    })
+    .collect();
+  test_forecast(periods)
+}
+
+#[test]
+fn periods_in_range() {
+  let forecast = test_hourly_forecast(3);
+  let start = forecast.properties.periods[0].start_time;
+  let next_ten = forecast.periods_in_range(start, start + chrono::Duration::hours(10));
+  assert_eq!(next_ten.len(), 3);
+  let second = forecast.periods_in_range(
+    start + chrono::Duration::hours(1),
+    start + chrono::Duration::hours(2),
+  );
+  assert_eq!(second.len(), 1);
+  assert_eq!(second[0].number, 2);
+  assert!(forecast
+    .periods_in_range(start - chrono::Duration::hours(2), start)
+    .is_empty());
+}
+
+#[test]
+fn forecast_is_newer_than() {
+  let old = test_forecast(vec![]);
+  let mut new = test_forecast(vec![]);
+  assert!(!new.is_newer_than(&old));
+  assert!(!old.is_newer_than(&new));
+  new.properties.generated_at += Duration::minutes(5);
+  assert!(new.is_newer_than(&old));
+  assert!(!old.is_newer_than(&new));
+}
+
+#[test]
+fn current_and_next_period() {
+  let mut forecast = test_hourly_forecast(3);
+  let start = forecast.properties.periods[0].start_time;
+  let at = |minutes| start + chrono::Duration::minutes(minutes);
+  assert_eq!(forecast.current(at(30)).unwrap().number, 1);
+  assert_eq!(forecast.next(at(30)).unwrap().number, 2);
+  assert_eq!(forecast.current(at(60)).unwrap().number, 2);
+  assert_eq!(forecast.current(at(150)).unwrap().number, 3);
+  assert!(forecast.next(at(150)).is_none());
+  assert!(forecast.current(at(180)).is_none());
+  // before the first period, and in a gap between periods
+  assert_eq!(forecast.current(at(-30)).unwrap().number, 1);
+  forecast.properties.periods[1].end_time = at(90);
+  assert_eq!(forecast.current(at(100)).unwrap().number, 3);
+  assert!(forecast.next(at(100)).is_none());
+}
+
+#[test]
+fn daily_summaries() {
+  let start = Local.with_ymd_and_hms(2021, 8, 21, 21, 0, 0).unwrap();
+  let periods = [
+    (70, "Clear", None),
+    (68, "Cloudy", Some(10)),
+    (67, "Cloudy", Some(20)),
+    (65, "Rain", Some(60)),
+    (66, "Cloudy", None),
+  ]
+  .into_iter()
+  .enumerate()
+  .map(|(i, (temperature, short_forecast, pop))| {
+    let mut p = test_period();
+    p.start_time = start + Duration::hours(i as i64 + 1);
+    p.end_time = start + Duration::hours(i as i64 + 2);
+    p.temperature = temperature;
+    p.short_forecast = short_forecast.to_string();
+    p.probability_of_precipitation = pop;
+    p
+  })
+  .collect();
+  let days = test_forecast(periods).daily_summaries();
+  assert_eq!(days.len(), 2);
+  assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2021, 8, 21).unwrap());
+  assert_eq!((days[0].high, days[0].low), (70, 68));
+  assert_eq!(days[0].short_forecast, "Clear");
+  assert_eq!(days[0].max_probability_of_precipitation, Some(10));
+  assert_eq!(days[1].date, NaiveDate::from_ymd_opt(2021, 8, 22).unwrap());
+  assert_eq!((days[1].high, days[1].low), (67, 65));
+  assert_eq!(days[1].short_forecast, "Cloudy");
+  assert_eq!(days[1].max_probability_of_precipitation, Some(60));
+  assert!(test_forecast(vec![]).daily_summaries().is_empty());
+}
+
+#[test]
+fn extremes() {
+  let mut forecast = test_hourly_forecast(3);
+  let periods = &mut forecast.properties.periods;
+  periods[0].temperature = 70;
+  // 25°C is 77°F, warmer than 75°F despite the smaller number
+  periods[1].temperature = 25;
+  periods[1].temperature_unit = "C".to_string();
+  periods[2].temperature = 75;
+  let (coldest, warmest) = forecast.extremes().unwrap();
+  assert_eq!((coldest.number, warmest.number), (1, 2));
+  assert!(test_forecast(vec![]).extremes().is_none());
+}
+
+#[test]
+fn merge_forecasts_by_period() {
+  let hourly = test_hourly_forecast(20);
+  let start = hourly.properties.periods[0].start_time;
+  let daily = [(1, 4), (4, 16), (16, 28)]
+    .into_iter()
+    .map(|(from, to)| {
+      let mut p = test_period();
+      p.start_time = start + Duration::hours(from);
+      p.end_time = start + Duration::hours(to);
+      p
+    })
+    .collect();
+  let combined = merge_forecasts(test_forecast(daily), hourly);
+  let numbers: Vec<Vec<u16>> = combined
+    .periods
+    .iter()
+    .map(|c| c.hourly.iter().map(|h| h.number).collect())
+    .collect();
+  assert_eq!(numbers[0], vec![2, 3, 4]);
+  assert_eq!(numbers[1], (5..=16).collect::<Vec<_>>());
+  assert_eq!(numbers[2], vec![17, 18, 19, 20]);
+}
+
+#[test]
+fn weather_bundle_json_round_trip() {
+  let city = City {
+    city: "New York".to_string(),
+    state_id: "NY".to_string(),
+    lat: 40.6943,
+    lng: -73.9249,
+  };
+  let bundle = WeatherBundle::new(city, test_hourly_forecast(3));
+  let file = tempfile::NamedTempFile::new().unwrap();
+  bundle.save_json(file.path()).unwrap();
+  assert_eq!(WeatherBundle::load_json(file.path()).unwrap(), bundle);
+}
+
+#[test]
+fn weather_bundles_geojson() {
+  let city = |city: &str, lat, lng| City {
+    city: city.to_string(),
+    state_id: "NY".to_string(),
+    lat,
+    lng,
+  };
+  let bundles = [
+    WeatherBundle::new(city("New York", 40.6943, -73.9249), test_hourly_forecast(3)),
+    WeatherBundle::new(city("Buffalo", 42.9016, -78.8487), test_hourly_forecast(0)),
+  ];
+  let geojson = to_geojson(&bundles);
+  assert_eq!(geojson["type"], "FeatureCollection");
+  let features = geojson["features"].as_array().unwrap();
+  assert_eq!(features.len(), 2);
+  let coords = features[0]["geometry"]["coordinates"].as_array().unwrap();
+  assert!((coords[0].as_f64().unwrap() - -73.9249).abs() < 1e-4);
+  assert!((coords[1].as_f64().unwrap() - 40.6943).abs() < 1e-4);
+  let props = &features[0]["properties"];
+  assert_eq!(props["city"], "New York");
+  assert_eq!(props["temperature"], 72);
+  assert_eq!(props["shortForecast"], "Chance Showers And Thunderstorms");
+  assert!(features[1]["properties"]["temperature"].is_null());
+}
+
+#[test]
+fn weather_bundle_missing_wind() {
+  let mut period = test_period();
+  period.wind_speed = None;
+  period.wind_direction = None;
+  let city = City {
+    city: "New York".to_string(),
+    state_id: "NY".to_string(),
+    lat: 40.6943,
+    lng: -73.9249,
+  };
+  let bundle = WeatherBundle::new(city, test_forecast(vec![period, test_period()]));
+  assert_eq!(bundle.forecast.len(), 2);
+  assert_eq!(bundle.forecast[0].wind_speed, None);
+  assert_eq!(bundle.forecast[0].wind_direction, None);
+  assert_eq!(bundle.forecast[1].wind_speed.as_deref(), Some("10 mph"));
+}
+
+#[test]
+fn weather_bundle_period_name() {
+  let city = City {
+    city: "New York".to_string(),
+    state_id: "NY".to_string(),
+    lat: 40.6943,
+    lng: -73.9249,
+  };
+  let period = test_period();
+  let bundle = WeatherBundle::new(city, test_forecast(vec![test_period()]));
+  assert_eq!(bundle.forecast[0].name.as_deref(), Some("Tonight"));
+  assert_eq!(
+    bundle.forecast[0].detailed_forecast.as_deref(),
+    Some(period.detailed_forecast.as_str())
+  );
+  let json = r#"{
+    "start": "2021-08-21T18:00:00-04:00",
+    "end": "2021-08-22T06:00:00-04:00",
+    "temperature": 72,
+    "wind_speed": null,
+    "wind_direction": null,
+    "short_forecast": "Clear"
+  }"#;
+  let old: ForecastBundle = serde_json::from_str(json).unwrap();
+  assert_eq!(old.name, None);
+}
+
+pub async fn get_point(pnt: &Point, client: &Client) -> Result<PointInfo, Error> {
+  WeatherClient::from(client.clone()).point(pnt).await
+}
+
+/// Find the City matching `name` and `state` (case-insensitive) and
+/// get its point, returning Error::CityNotFound if there is no match.
+pub async fn get_point_by_name(
+  cities: &[City],
+  name: &str,
+  state: &str,
+  client: &Client,
+) -> Result<PointInfo, Error> {
+  WeatherClient::from(client.clone())
+    .point_by_name(cities, name, state)
+    .await
+}
+
+pub async fn get_forecast(pnt: &PointInfo, client: &Client) -> Result<Forecast, Error> {
+  WeatherClient::from(client.clone()).forecast(pnt).await
+}
+
+/// Get the forecast of a Point, looking up its PointInfo first
+pub async fn forecast_for_point(pnt: &Point, client: &Client) -> Result<Forecast, Error> {
+  WeatherClient::from(client.clone())
+    .forecast_for_point(pnt)
+    .await
+}
+
+/// Get the forecast of a known gridpoint without looking up its
+/// point first, e.g. ("OKX", 33, 35)
+pub async fn get_forecast_by_grid(
+  office: &str,
+  x: u32,
+  y: u32,
+  client: &Client,
+) -> Result<Forecast, Error> {
+  WeatherClient::from(client.clone())
+    .forecast_by_grid(office, x, y)
+    .await
+}
+
+/// Get the forecast of a point with timestamps in the forecast
+/// location's own UTC offset
+pub async fn get_forecast_with_offset(
+  pnt: &PointInfo,
+  client: &Client,
+) -> Result<Forecast<FixedOffset>, Error> {
+  WeatherClient::from(client.clone())
+    .forecast_with_offset(pnt)
+    .await
+}
+
+pub async fn get_forecast_hourly(pnt: &PointInfo, client: &Client) -> Result<Forecast, Error> {
+  WeatherClient::from(client.clone())
+    .forecast_hourly(pnt)
+    .await
+}
+
+/// Same as get_forecast, requesting the given Units (or the API
+/// default if None)
+pub async fn get_forecast_with_units(
+  pnt: &PointInfo,
+  units: Option<Units>,
+  client: &Client,
+) -> Result<Forecast, Error> {
+  WeatherClient::from(client.clone())
+    .forecast_with_units(pnt, units)
+    .await
+}
+
+/// Same as get_forecast_hourly, requesting the given Units (or the
+/// API default if None)
+pub async fn get_forecast_hourly_with_units(
+  pnt: &PointInfo,
+  units: Option<Units>,
+  client: &Client,
+) -> Result<Forecast, Error> {
+  WeatherClient::from(client.clone())
+    .forecast_hourly_with_units(pnt, units)
+    .await
+}
+
+/// Unit system of a forecast, reflected in ForecastProps.units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+  /// US customary units (°F, mph)
+  Us,
+  /// SI units (°C, km/h)
+  Si,
+}
+
+impl Units {
+  /// Value of the `units` query parameter
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Units::Us => "us",
+      Units::Si => "si",
+    }
+  }
+}
+
+/// Append the `units` query parameter to a forecast URL
+fn with_units(url: &str, units: Option<Units>) -> String {
+  match units {
+    Some(units) => {
+      let sep = if url.contains('?') { '&' } else { '?' };
+      format!("{}{}units={}", url, sep, units.as_str())
+    }
+    None => url.to_string(),
+  }
+}
+
+#[test]
+fn units_query_string() {
+  let url = "https://api.weather.gov/gridpoints/OKX/33,35/forecast";
+  assert_eq!(with_units(url, None), url);
+  assert_eq!(
+    with_units(url, Some(Units::Si)),
+    "https://api.weather.gov/gridpoints/OKX/33,35/forecast?units=si"
+  );
+  assert_eq!(
+    with_units(url, Some(Units::Us)),
+    "https://api.weather.gov/gridpoints/OKX/33,35/forecast?units=us"
+  );
+  assert_eq!(
+    with_units("https://example.com/forecast?a=1", Some(Units::Si)),
+    "https://example.com/forecast?a=1&units=si"
+  );
+}
+
+/// Result of GET /gridpoints/{office}/{x},{y}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GridData {
+  pub properties: GridProps,
+}
+
+/// Inner properties object of GridData
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GridProps {
+  #[serde(rename(deserialize = "updateTime"))]
+  pub update_time: DateTime<Local>,
+  #[serde(default)]
+  pub temperature: GridSeries,
+  #[serde(default)]
+  pub dewpoint: GridSeries,
+  #[serde(rename(deserialize = "relativeHumidity"), default)]
+  pub relative_humidity: GridSeries,
+  #[serde(rename(deserialize = "apparentTemperature"), default)]
+  pub apparent_temperature: GridSeries,
+  #[serde(rename(deserialize = "windSpeed"), default)]
+  pub wind_speed: GridSeries,
+  #[serde(rename(deserialize = "windDirection"), default)]
+  pub wind_direction: GridSeries,
+  #[serde(rename(deserialize = "probabilityOfPrecipitation"), default)]
+  pub probability_of_precipitation: GridSeries,
+}
+
+impl GridData {
+  /// Relative humidity values, in percent
+  pub fn relative_humidity(&self) -> &[GridValue] {
+    &self.properties.relative_humidity.values
+  }
+
+  /// Apparent ("feels like") temperature valid at a given time, in
+  /// the apparent_temperature series' uom (typically wmoUnit:degC).
+  /// None if no interval contains `at` or its value is null.
+  pub fn apparent_temperature_at(&self, at: DateTime<Local>) -> Option<f64> {
+    self.properties.apparent_temperature.at(at)?.value
+  }
+}
+
+/// Time-series quantity of GridProps
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GridSeries {
+  pub uom: Option<String>,
+  pub values: Vec<GridValue>,
+}
+
+impl GridSeries {
+  /// The value valid at a given time, if any
+  pub fn at(&self, at: DateTime<Local>) -> Option<&GridValue> {
+    self.values.iter().find(|v| v.start <= at && at < v.end())
+  }
+}
+
+/// Single instance of item in values object of GridSeries, valid from
+/// `start` for `duration`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "GridSeriesValue", into = "GridSeriesValue")]
+pub struct GridValue {
+  pub start: DateTime<Local>,
+  pub duration: Duration,
+  pub value: Option<f64>,
+}
+
+impl GridValue {
+  /// End of the interval the value is valid for
+  pub fn end(&self) -> DateTime<Local> {
+    self.start + self.duration
+  }
+}
+
+/// Raw form of GridValue, as it appears in the values object of
+/// GridSeries
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GridSeriesValue {
+  /// ISO-8601 interval, e.g. "2021-08-21T18:00:00+00:00/PT1H"
+  #[serde(rename(deserialize = "validTime"), alias = "valid_time")]
+  pub valid_time: String,
+  pub value: Option<f64>,
+}
+
+impl TryFrom<GridSeriesValue> for GridValue {
+  type Error = Error;
+  fn try_from(raw: GridSeriesValue) -> Result<Self, Self::Error> {
+    let (start, duration) = parse_valid_time(&raw.valid_time)?;
+    Ok(GridValue {
+      start,
+      duration,
+      value: raw.value,
+    })
+  }
+}
+
+impl From<GridValue> for GridSeriesValue {
+  fn from(v: GridValue) -> Self {
+    GridSeriesValue {
+      valid_time: format!("{}/{}", v.start.to_rfc3339(), format_duration(v.duration)),
+      value: v.value,
+    }
+  }
+}
+
+/// Parse a gridpoint validTime interval, e.g.
+/// "2021-08-21T18:00:00+00:00/P1DT6H", into its start and duration
+pub fn parse_valid_time(s: &str) -> Result<(DateTime<Local>, Duration), Error> {
+  let invalid = || Error::InvalidValidTime(s.to_string());
+  let (start, duration) = s.split_once('/').ok_or_else(invalid)?;
+  let start = DateTime::parse_from_rfc3339(start).map_err(|_| invalid())?;
+  let duration = parse_duration(duration).ok_or_else(invalid)?;
+  Ok((start.with_timezone(&Local), duration))
+}
+
+/// Parse an ISO-8601 duration, e.g. "PT6H", "P7D" or "P1DT12H".
+/// chrono::Duration has no calendar units, so a year is taken as 365
+/// days and a month as 30 days.
+fn parse_duration(s: &str) -> Option<Duration> {
+  let (date, time) = match s.strip_prefix('P')?.split_once('T') {
+    Some((_, "")) => return None,
+    Some((date, time)) => (date, time),
+    None => (s.strip_prefix('P')?, ""),
+  };
+  if date.is_empty() && time.is_empty() {
+    return None;
+  }
+  let mut duration = Duration::zero();
+  for (part, units) in [(date, "YMWD"), (time, "HMS")] {
+    let mut rest = part;
+    let mut seen = 0;
+    while !rest.is_empty() {
+      let i = rest.find(|c: char| !c.is_ascii_digit())?;
+      let n: i64 = rest[..i].parse().ok()?;
+      let unit = rest[i..].chars().next()?;
+      // units must be in order and not repeated
+      let pos = units[seen..].find(unit)? + seen;
+      seen = pos + 1;
+      let secs: i64 = match (units, unit) {
+        ("YMWD", 'Y') => 365 * 24 * 60 * 60,
+        ("YMWD", 'M') => 30 * 24 * 60 * 60,
+        (_, 'W') => 7 * 24 * 60 * 60,
+        (_, 'D') => 24 * 60 * 60,
+        (_, 'H') => 60 * 60,
+        (_, 'M') => 60,
+        _ => 1,
       };
-      vec.push(i);
+      // the Duration constructors panic out of range, milliseconds
+      // doesn't for any i64
+      let ms = n.checked_mul(secs)?.checked_mul(1000)?;
+      duration = duration.checked_add(&Duration::milliseconds(ms))?;
+      rest = &rest[i + 1..];
+    }
+  }
+  Some(duration)
+}
+
+/// Format a Duration as an ISO-8601 duration, e.g. "P1DT6H"
+fn format_duration(duration: Duration) -> String {
+  let days = duration.num_days();
+  let secs = (duration - Duration::days(days)).num_seconds();
+  let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+  let mut out = String::from("P");
+  if days > 0 {
+    out.push_str(&format!("{}D", days));
+  }
+  if secs > 0 || days == 0 {
+    out.push('T');
+    if h > 0 || secs == 0 {
+      out.push_str(&format!("{}H", h));
+    }
+    if m > 0 {
+      out.push_str(&format!("{}M", m));
+    }
+    if s > 0 {
+      out.push_str(&format!("{}S", s));
+    }
+  }
+  out
+}
+
+#[test]
+fn valid_time_durations() {
+  let start = DateTime::parse_from_rfc3339("2021-08-21T18:00:00+00:00").unwrap();
+  for (s, duration) in [
+    ("PT1H", Duration::hours(1)),
+    ("PT6H", Duration::hours(6)),
+    ("P1D", Duration::days(1)),
+    ("P1DT12H", Duration::hours(36)),
+    ("PT30M", Duration::minutes(30)),
+  ] {
+    let parsed = parse_valid_time(&format!("2021-08-21T18:00:00+00:00/{}", s)).unwrap();
+    assert_eq!(parsed, (start.with_timezone(&Local), duration));
+    assert_eq!(format_duration(duration), s);
+  }
+  for (s, duration) in [
+    ("P7D", Duration::days(7)),
+    ("P1W", Duration::weeks(1)),
+    ("P1M", Duration::days(30)),
+    ("P1Y2M", Duration::days(425)),
+    ("PT1H30M15S", Duration::seconds(5415)),
+  ] {
+    let parsed = parse_valid_time(&format!("2021-08-21T18:00:00+00:00/{}", s)).unwrap();
+    assert_eq!(parsed.1, duration, "{}", s);
+  }
+  for s in [
+    "P",
+    "PT",
+    "P1DT",
+    "PT1",
+    "PT1X",
+    "PT1M1H",
+    "P1D1Y",
+    "PT1Y",
+    "1H",
+    "P99999999999999D",
+    "PT9223372036854775807S",
+    "P99999999999999999999Y",
+  ] {
+    assert!(
+      parse_valid_time(&format!("2021-08-21T18:00:00+00:00/{}", s)).is_err(),
+      "{}",
+      s
+    );
+  }
+}
+
+#[test]
+fn grid_value_at() {
+  let series: GridSeries = serde_json::from_str(
+    r#"{
+      "uom": "wmoUnit:percent",
+      "values": [
+        {"validTime": "2021-08-21T19:00:00+00:00/PT1H", "value": 80},
+        {"validTime": "2021-08-21T20:00:00+00:00/PT1H", "value": 75}
+      ]
+    }"#,
+  )
+  .unwrap();
+  let at = |s: &str| {
+    DateTime::parse_from_rfc3339(s)
+      .unwrap()
+      .with_timezone(&Local)
+  };
+  assert_eq!(series.values[0].duration, Duration::hours(1));
+  assert_eq!(series.values[0].end(), at("2021-08-21T20:00:00+00:00"));
+  assert_eq!(
+    series.at(at("2021-08-21T20:30:00+00:00")).unwrap().value,
+    Some(75.0)
+  );
+  assert!(series.at(at("2021-08-21T21:00:00+00:00")).is_none());
+  assert!(matches!(
+    parse_valid_time("2021-08-21T19:00:00+00:00"),
+    Err(Error::InvalidValidTime(_))
+  ));
+  let json = serde_json::to_value(&series.values[0]).unwrap();
+  assert_eq!(
+    serde_json::from_value::<GridValue>(json).unwrap(),
+    series.values[0]
+  );
+}
+
+pub async fn get_grid_data(pnt: &PointInfo, client: &Client) -> Result<GridData, Error> {
+  WeatherClient::from(client.clone()).grid_data(pnt).await
+}
+
+#[test]
+fn grid_data() {
+  let grid: GridData = serde_json::from_str(
+    r#"{
+      "properties": {
+        "updateTime": "2021-08-21T19:38:19+00:00",
+        "temperature": {
+          "uom": "wmoUnit:degC",
+          "values": [
+            {"validTime": "2021-08-21T19:00:00+00:00/PT1H", "value": 28.333333333333332},
+            {"validTime": "2021-08-21T20:00:00+00:00/PT2H", "value": null}
+          ]
+        },
+        "windSpeed": {
+          "uom": "wmoUnit:km_h-1",
+          "values": [{"validTime": "2021-08-21T19:00:00+00:00/PT3H", "value": 11.112}]
+        }
+      }
+    }"#,
+  )
+  .unwrap();
+  let props = grid.properties;
+  assert_eq!(props.temperature.uom.as_deref(), Some("wmoUnit:degC"));
+  assert_eq!(props.temperature.values.len(), 2);
+  assert_eq!(props.temperature.values[1].value, None);
+  assert_eq!(
+    props.wind_speed.values[0].start,
+    DateTime::parse_from_rfc3339("2021-08-21T19:00:00+00:00").unwrap()
+  );
+  assert_eq!(props.wind_speed.values[0].duration, Duration::hours(3));
+  assert!(props.apparent_temperature.values.is_empty());
+}
+
+#[test]
+fn apparent_temperature_at() {
+  let grid: GridData = serde_json::from_str(
+    r#"{
+      "properties": {
+        "updateTime": "2021-08-21T19:38:19+00:00",
+        "apparentTemperature": {
+          "uom": "wmoUnit:degC",
+          "values": [
+            {"validTime": "2021-08-21T19:00:00+00:00/PT1H", "value": 31.1},
+            {"validTime": "2021-08-21T20:00:00+00:00/PT2H", "value": 30.0},
+            {"validTime": "2021-08-21T22:00:00+00:00/PT1H", "value": null}
+          ]
+        }
+      }
+    }"#,
+  )
+  .unwrap();
+  let at = |s: &str| {
+    DateTime::parse_from_rfc3339(s)
+      .unwrap()
+      .with_timezone(&Local)
+  };
+  assert_eq!(
+    grid.apparent_temperature_at(at("2021-08-21T19:59:59+00:00")),
+    Some(31.1)
+  );
+  assert_eq!(
+    grid.apparent_temperature_at(at("2021-08-21T21:30:00+00:00")),
+    Some(30.0)
+  );
+  assert_eq!(
+    grid.apparent_temperature_at(at("2021-08-21T22:00:00+00:00")),
+    None
+  );
+  assert_eq!(
+    grid.apparent_temperature_at(at("2021-08-21T18:00:00+00:00")),
+    None
+  );
+}
+
+/// GeoJSON Point geometry. Note that coordinates are in (lng, lat)
+/// order.
+#[derive(Debug, Deserialize)]
+struct PointGeometry {
+  coordinates: (Longitude, Latitude),
+}
+
+impl From<PointGeometry> for Point {
+  fn from(geometry: PointGeometry) -> Self {
+    let (lng, lat) = geometry.coordinates;
+    Point::from_coordinates(lat, lng)
+  }
+}
+
+/// Result of GET /gridpoints/{office}/{x},{y}/stations
+#[derive(Debug, Deserialize)]
+pub struct StationCollection {
+  pub features: Vec<Station>,
+  #[serde(default)]
+  pub pagination: Option<Pagination>,
+}
+
+/// Pagination object of a paginated listing
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Pagination {
+  /// URL of the next page
+  pub next: Option<String>,
+}
+
+/// Observation station, flattened from a single feature of
+/// StationCollection. Only Deserialize is derived, since the
+/// flattened form can't be read back as a feature.
+#[derive(Debug, Deserialize)]
+#[serde(from = "StationFeature")]
+pub struct Station {
+  pub station_identifier: String,
+  pub name: String,
+  pub point: Point,
+}
+
+#[derive(Deserialize)]
+struct StationFeature {
+  geometry: PointGeometry,
+  properties: StationProps,
+}
+
+#[derive(Deserialize)]
+struct StationProps {
+  #[serde(rename = "stationIdentifier")]
+  station_identifier: String,
+  name: String,
+}
+
+impl From<StationFeature> for Station {
+  fn from(feature: StationFeature) -> Self {
+    Station {
+      station_identifier: feature.properties.station_identifier,
+      name: feature.properties.name,
+      point: feature.geometry.into(),
     }
-    WeatherBundle {
-      location: loc,
-      forecast: vec,
-      updated: fcb.properties.updated,
+  }
+}
+
+/// The `k` stations closest to a Point, closest first
+pub fn nearest_stations<'a>(stations: &'a [Station], point: &Point, k: usize) -> Vec<&'a Station> {
+  let mut by_distance: Vec<(f32, &Station)> = stations
+    .iter()
+    .map(|s| (point.earth_distance_from(&s.point), s))
+    .collect();
+  by_distance.sort_by(|a, b| a.0.total_cmp(&b.0));
+  by_distance.into_iter().take(k).map(|(_, s)| s).collect()
+}
+
+#[test]
+fn nearest_two_stations() {
+  let station = |id: &str, lat, lng| Station {
+    station_identifier: id.to_string(),
+    name: id.to_string(),
+    point: Point::new(lat, lng),
+  };
+  let stations = [
+    station("KBOS", 42.3606, -71.0097),
+    station("KJFK", 40.6392, -73.7639),
+    station("KORD", 41.9602, -87.9316),
+    station("KNYC", 40.7789, -73.9692),
+    station("KLGA", 40.7794, -73.8803),
+  ];
+  let nearest = nearest_stations(&stations, &Point::new(40.7128, -74.006), 2);
+  let ids: Vec<&str> = nearest
+    .iter()
+    .map(|s| s.station_identifier.as_str())
+    .collect();
+  assert_eq!(ids, ["KNYC", "KLGA"]);
+  assert_eq!(nearest_stations(&stations, &Point::default(), 10).len(), 5);
+}
+
+/// Get the observation stations near a point, closest first
+pub async fn get_observation_stations(
+  pnt: &PointInfo,
+  client: &Client,
+) -> Result<Vec<Station>, Error> {
+  WeatherClient::from(client.clone())
+    .observation_stations(pnt)
+    .await
+}
+
+#[test]
+fn observation_stations() {
+  let res: StationCollection = serde_json::from_str(
+    r#"{
+      "type": "FeatureCollection",
+      "features": [
+        {
+          "id": "https://api.weather.gov/stations/KNYC",
+          "type": "Feature",
+          "geometry": {"type": "Point", "coordinates": [-73.98128, 40.77898]},
+          "properties": {
+            "@id": "https://api.weather.gov/stations/KNYC",
+            "elevation": {"unitCode": "wmoUnit:m", "value": 48.768},
+            "stationIdentifier": "KNYC",
+            "name": "New York City, Central Park",
+            "timeZone": "America/New_York"
+          }
+        },
+        {
+          "id": "https://api.weather.gov/stations/KLGA",
+          "type": "Feature",
+          "geometry": {"type": "Point", "coordinates": [-73.88, 40.77944]},
+          "properties": {
+            "@id": "https://api.weather.gov/stations/KLGA",
+            "elevation": {"unitCode": "wmoUnit:m", "value": 3.9624},
+            "stationIdentifier": "KLGA",
+            "name": "New York, La Guardia Airport",
+            "timeZone": "America/New_York"
+          }
+        }
+      ]
+    }"#,
+  )
+  .unwrap();
+  let ids: Vec<&str> = res
+    .features
+    .iter()
+    .map(|s| s.station_identifier.as_str())
+    .collect();
+  assert_eq!(ids, ["KNYC", "KLGA"]);
+  assert_eq!(res.features[0].name, "New York City, Central Park");
+  assert_eq!(res.features[0].point, Point::new(40.77898, -73.98128));
+}
+
+/// Single GeoJSON feature, used where only the properties object is
+/// of interest
+#[derive(Deserialize)]
+struct Feature<T> {
+  properties: T,
+}
+
+/// Single page of a paginated GeoJSON feature collection
+#[derive(Deserialize)]
+struct Page<T> {
+  features: Vec<T>,
+  #[serde(default)]
+  pagination: Option<Pagination>,
+}
+
+/// Result of GET /stations/{id}/observations/latest
+///
+/// Temperatures are in degrees Celsius, wind speed in km/h, wind
+/// direction in degrees, and barometric pressure in pascals. Sensors
+/// that did not report are None.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Observation {
+  pub timestamp: DateTime<Local>,
+  #[serde(deserialize_with = "de_unit_value", default)]
+  pub temperature: Option<f32>,
+  #[serde(deserialize_with = "de_unit_value", default)]
+  pub dewpoint: Option<f32>,
+  #[serde(
+    rename(deserialize = "windSpeed"),
+    deserialize_with = "de_unit_value",
+    default
+  )]
+  pub wind_speed: Option<f32>,
+  #[serde(
+    rename(deserialize = "windDirection"),
+    deserialize_with = "de_unit_value",
+    default
+  )]
+  pub wind_direction: Option<f32>,
+  #[serde(
+    rename(deserialize = "barometricPressure"),
+    deserialize_with = "de_unit_value",
+    default
+  )]
+  pub barometric_pressure: Option<f32>,
+  #[serde(
+    rename(deserialize = "relativeHumidity"),
+    deserialize_with = "de_unit_value",
+    default
+  )]
+  pub relative_humidity: Option<f32>,
+}
+
+/// Get the observations of a station from `start` to `end`, newest
+/// first
+pub async fn get_observations(
+  station_id: &str,
+  start: DateTime<Local>,
+  end: DateTime<Local>,
+  client: &Client,
+) -> Result<Vec<Observation>, Error> {
+  WeatherClient::from(client.clone())
+    .observations(station_id, start, end)
+    .await
+}
+
+/// Get the latest observation from a station, given its identifier
+/// (e.g. "KNYC").
+pub async fn get_latest_observation(
+  station_id: &str,
+  client: &Client,
+) -> Result<Observation, Error> {
+  WeatherClient::from(client.clone())
+    .latest_observation(station_id)
+    .await
+}
+
+#[test]
+fn latest_observation() {
+  let res: Feature<Observation> = serde_json::from_str(
+    r#"{
+      "id": "https://api.weather.gov/stations/KNYC/observations/2021-08-21T19:51:00+00:00",
+      "type": "Feature",
+      "geometry": {"type": "Point", "coordinates": [-73.98, 40.78]},
+      "properties": {
+        "station": "https://api.weather.gov/stations/KNYC",
+        "timestamp": "2021-08-21T19:51:00+00:00",
+        "textDescription": "Cloudy",
+        "temperature": {"unitCode": "wmoUnit:degC", "value": 26.1, "qualityControl": "V"},
+        "dewpoint": {"unitCode": "wmoUnit:degC", "value": 21.7, "qualityControl": "V"},
+        "windDirection": {"unitCode": "wmoUnit:degree_(angle)", "value": null, "qualityControl": "Z"},
+        "windSpeed": {"unitCode": "wmoUnit:km_h-1", "value": null, "qualityControl": "Z"},
+        "barometricPressure": {"unitCode": "wmoUnit:Pa", "value": 101590, "qualityControl": "V"},
+        "relativeHumidity": {"unitCode": "wmoUnit:percent", "value": 76.6, "qualityControl": "V"}
+      }
+    }"#,
+  )
+  .unwrap();
+  let obs = res.properties;
+  assert_eq!(obs.temperature, Some(26.1));
+  assert_eq!(obs.dewpoint, Some(21.7));
+  assert_eq!(obs.wind_speed, None);
+  assert_eq!(obs.wind_direction, None);
+  assert_eq!(obs.barometric_pressure, Some(101590.0));
+  assert_eq!(obs.relative_humidity, Some(76.6));
+}
+
+/// Result of GET /radar/stations/{id}, flattened from its feature.
+/// Only Deserialize is derived, since the flattened form can't be read
+/// back as a feature.
+#[derive(Debug, Deserialize)]
+#[serde(from = "RadarStationFeature")]
+pub struct RadarStation {
+  pub id: String,
+  pub name: String,
+  pub point: Point,
+  /// Elevation in meters
+  pub elevation: Option<f64>,
+  /// Status of the Radar Data Acquisition unit (e.g. "Operate"), if
+  /// reported
+  pub rda_status: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RadarStationFeature {
+  geometry: PointGeometry,
+  properties: RadarStationProps,
+}
+
+#[derive(Deserialize)]
+struct RadarStationProps {
+  id: String,
+  name: String,
+  elevation: Option<Measurement>,
+  #[serde(default)]
+  rda: Option<Feature<RdaProps>>,
+}
+
+#[derive(Deserialize)]
+struct RdaProps {
+  status: Option<String>,
+}
+
+impl From<RadarStationFeature> for RadarStation {
+  fn from(feature: RadarStationFeature) -> Self {
+    let p = feature.properties;
+    RadarStation {
+      id: p.id,
+      name: p.name,
+      point: feature.geometry.into(),
+      elevation: p.elevation.and_then(|e| e.as_meters()),
+      rda_status: p.rda.and_then(|rda| rda.properties.status),
     }
   }
 }
 
-pub async fn get_point(pnt: &Point, client: &Client) -> Result<PointInfo, Error> {
-  let mut url: String = String::from("http://api.weather.gov/");
-  for i in &["points/", &pnt.lat.to_string(), ",", &pnt.lng.to_string()] {
-    url.push_str(i);
+/// Get the metadata of a radar station, given its ID
+/// (e.g. PointProps.radar_station)
+pub async fn get_radar_station(id: &str, client: &Client) -> Result<RadarStation, Error> {
+  WeatherClient::from(client.clone()).radar_station(id).await
+}
+
+#[test]
+fn radar_station_deserialize() {
+  let station: RadarStation = serde_json::from_str(
+    r#"{
+      "id": "https://api.weather.gov/radar/stations/KOKX",
+      "type": "Feature",
+      "geometry": {"type": "Point", "coordinates": [-72.8639, 40.8655]},
+      "properties": {
+        "id": "KOKX",
+        "name": "New York City",
+        "stationType": "WSR-88D",
+        "elevation": {"unitCode": "wmoUnit:m", "value": 25.9080},
+        "timeZone": "America/New_York",
+        "rda": {
+          "timestamp": "2021-08-21T20:10:46+00:00",
+          "reportingHost": "rds",
+          "properties": {
+            "resolutionVersion": null,
+            "volumeCoveragePattern": "R35",
+            "controlStatus": "RPG Remote",
+            "mode": "Operational",
+            "status": "Operate"
+          }
+        }
+      }
+    }"#,
+  )
+  .unwrap();
+  assert_eq!(station.id, "KOKX");
+  assert_eq!(station.name, "New York City");
+  assert_eq!(station.point, Point::new(40.8655, -72.8639));
+  assert_eq!(station.elevation, Some(25.908));
+  assert_eq!(station.rda_status.as_deref(), Some("Operate"));
+  let station: RadarStation = serde_json::from_str(
+    r#"{
+      "geometry": {"type": "Point", "coordinates": [-72.8639, 40.8655]},
+      "properties": {"id": "KOKX", "name": "New York City", "elevation": null, "rda": null}
+    }"#,
+  )
+  .unwrap();
+  assert_eq!(station.elevation, None);
+  assert_eq!(station.rda_status, None);
+}
+
+/// Result of GET /offices/{id}, a Weather Forecast Office
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Office {
+  pub id: String,
+  /// City the office is named for, e.g. "New York, NY"
+  pub name: String,
+  pub address: Address,
+  pub telephone: Option<String>,
+  pub email: Option<String>,
+}
+
+impl fmt::Display for Office {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "NWS {}", self.name)
   }
-  let response = client.get(&url).send().await?;
-  let body = response.text().await?;
-  debug!("{}", body);
-  let res: PointInfo = serde_json::from_str(&body)?;
-  Ok(res)
 }
 
-pub async fn get_forecast(pnt: &PointInfo, client: &Client) -> Result<Forecast, Error> {
-  let response = client.get(&pnt.properties.forecast).send().await?;
-  let body = response.text().await?;
-  debug!("{}", body);
-  let res: Forecast = serde_json::from_str(&body)?;
-  Ok(res)
+/// Postal address of an Office
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Address {
+  #[serde(rename(deserialize = "streetAddress"))]
+  pub street_address: String,
+  #[serde(rename(deserialize = "addressLocality"))]
+  pub locality: String,
+  #[serde(rename(deserialize = "addressRegion"))]
+  pub region: String,
+  #[serde(rename(deserialize = "postalCode"))]
+  pub postal_code: String,
 }
 
-pub async fn get_forecast_hourly(pnt: &PointInfo, client: &Client) -> Result<Forecast, Error> {
-  let response = client.get(&pnt.properties.forecast_hourly).send().await?;
-  let body = response.text().await?;
-  let res: Forecast = serde_json::from_str(&body)?;
-  Ok(res)
+impl fmt::Display for Address {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{}, {}, {} {}",
+      self.street_address, self.locality, self.region, self.postal_code
+    )
+  }
 }
 
-/// TODO [2021-08-21] - get_alerts
-pub async fn get_alerts(_state: &str) -> Result<(), Error> {
-  Ok(())
+/// Get the forecast office responsible for a point
+pub async fn get_office(pnt: &PointInfo, client: &Client) -> Result<Office, Error> {
+  WeatherClient::from(client.clone()).office(pnt).await
 }
 
-pub async fn weather_report(lat: f32, lng: f32) -> Result<(), Error> {
-  let client = Client::builder().user_agent("thunderman").build()?;
-
-  let point = Point { lat, lng };
-
-  let res = get_point(&point, &client).await?;
-  let resf = get_forecast_hourly(&res, &client).await?;
-  for i in resf.properties.periods[0..10].into_iter() {
-    println!(
-      "{:#?}-{:#?} = {:#?}°F :: {:#?}",
-      &i.start_time.time(),
-      &i.end_time.time(),
-      &i.temperature,
-      &i.short_forecast
-    );
+#[test]
+fn office_deserialize() {
+  let office: Office = serde_json::from_str(
+    r#"{
+      "@context": {"@version": "1.1"},
+      "@type": "GovernmentOrganization",
+      "@id": "https://api.weather.gov/offices/OKX",
+      "id": "OKX",
+      "name": "New York, NY",
+      "address": {
+        "@type": "PostalAddress",
+        "streetAddress": "175 Brookhaven Avenue",
+        "addressLocality": "Upton",
+        "addressRegion": "NY",
+        "postalCode": "11973"
+      },
+      "telephone": "631-924-0517",
+      "faxNumber": "631-924-0517",
+      "email": "w-okx.webmaster@noaa.gov",
+      "sameAs": "https://www.weather.gov/okx",
+      "nwsRegion": "er",
+      "parentOrganization": "https://api.weather.gov/offices/ERH",
+      "responsibleCounties": ["https://api.weather.gov/zones/county/NYC061"]
+    }"#,
+  )
+  .unwrap();
+  assert_eq!(office.id, "OKX");
+  assert_eq!(
+    format!("Issued by {}", office),
+    "Issued by NWS New York, NY"
+  );
+  assert_eq!(
+    office.address.to_string(),
+    "175 Brookhaven Avenue, Upton, NY 11973"
+  );
+  assert_eq!(office.telephone.as_deref(), Some("631-924-0517"));
+  assert_eq!(office.email.as_deref(), Some("w-okx.webmaster@noaa.gov"));
+}
+
+/// Result of GET /products/types/{type}/locations/{office}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProductCollection {
+  #[serde(rename(deserialize = "@graph"))]
+  pub products: Vec<Product>,
+}
+
+/// Text product (e.g. an Area Forecast Discussion) listed in a
+/// ProductCollection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Product {
+  pub id: String,
+  #[serde(rename(deserialize = "wmoCollectiveId"))]
+  pub wmo_collective_id: String,
+  #[serde(rename(deserialize = "issuanceTime"))]
+  pub issuance_time: DateTime<Local>,
+}
+
+/// Result of GET /products/{id}
+#[derive(Debug, Deserialize)]
+struct ProductText {
+  #[serde(rename = "productText")]
+  product_text: String,
+}
+
+/// Get the text products of a type issued by an office, newest first
+/// (e.g. "AFD" and "OKX" for New York's Area Forecast Discussions)
+pub async fn get_products(
+  product_type: &str,
+  office: &str,
+  client: &Client,
+) -> Result<Vec<Product>, Error> {
+  WeatherClient::from(client.clone())
+    .products(product_type, office)
+    .await
+}
+
+/// Get the full text of a product, given its ID
+pub async fn get_product_text(id: &str, client: &Client) -> Result<String, Error> {
+  WeatherClient::from(client.clone()).product_text(id).await
+}
+
+/// Result of GET /zones/{type}/{id}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Zone {
+  pub id: String,
+  pub name: String,
+  pub state: Option<String>,
+  #[serde(rename(deserialize = "type"))]
+  pub zone_type: String,
+}
+
+/// Get the metadata of a zone, given its URL (e.g.
+/// PointProps.forecast_zone or PointProps.county)
+pub async fn get_zone(url: &str, client: &Client) -> Result<Zone, Error> {
+  WeatherClient::from(client.clone()).zone(url).await
+}
+
+/// Get the fire weather zone of a point
+pub async fn get_fire_weather_zone(pnt: &PointInfo, client: &Client) -> Result<Zone, Error> {
+  WeatherClient::from(client.clone())
+    .fire_weather_zone(pnt)
+    .await
+}
+
+/// Get the text forecast of a point's fire weather zone
+pub async fn get_fire_weather_forecast(
+  pnt: &PointInfo,
+  client: &Client,
+) -> Result<ZoneForecast, Error> {
+  WeatherClient::from(client.clone())
+    .fire_weather_forecast(pnt)
+    .await
+}
+
+/// Result of GET /zones/{type}/{id}/forecast
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneForecast {
+  pub properties: ZoneForecastProps,
+}
+
+/// Inner properties object of ZoneForecast
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneForecastProps {
+  pub zone: String,
+  pub updated: DateTime<Local>,
+  pub periods: Vec<ZoneForecastPeriod>,
+}
+
+/// Single instance of item in periods object of ZoneForecastProps
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneForecastPeriod {
+  pub number: u16,
+  pub name: String,
+  #[serde(rename(deserialize = "detailedForecast"))]
+  pub detailed_forecast: String,
+}
+
+#[test]
+fn fire_weather_zone() {
+  let res: Feature<Zone> = serde_json::from_str(
+    r#"{
+      "id": "https://api.weather.gov/zones/fire/CAZ277",
+      "type": "Feature",
+      "geometry": null,
+      "properties": {
+        "@id": "https://api.weather.gov/zones/fire/CAZ277",
+        "@type": "wx:Zone",
+        "id": "CAZ277",
+        "type": "fire",
+        "name": "Mendocino National Forest",
+        "effectiveDate": "2021-07-20T00:00:00+00:00",
+        "expirationDate": "2200-01-01T00:00:00+00:00",
+        "state": "CA",
+        "cwa": ["EKA"],
+        "forecastOffices": ["https://api.weather.gov/offices/EKA"],
+        "timeZone": ["America/Los_Angeles"],
+        "observationStations": [],
+        "radarStation": null
+      }
+    }"#,
+  )
+  .unwrap();
+  assert_eq!(res.properties.id, "CAZ277");
+  assert_eq!(res.properties.zone_type, "fire");
+  let forecast: ZoneForecast = serde_json::from_str(
+    r#"{
+      "properties": {
+        "zone": "https://api.weather.gov/zones/fire/CAZ277",
+        "updated": "2021-08-21T20:41:00+00:00",
+        "periods": [
+          {
+            "number": 1,
+            "name": "Tonight",
+            "detailedForecast": "Clear. Lows 45 to 55. Northwest winds 5 to 10 mph."
+          }
+        ]
+      }
+    }"#,
+  )
+  .unwrap();
+  assert_eq!(forecast.properties.periods.len(), 1);
+  assert_eq!(forecast.properties.periods[0].name, "Tonight");
+}
+
+#[test]
+fn zone() {
+  let res: Feature<Zone> = serde_json::from_str(
+    r#"{
+      "id": "https://api.weather.gov/zones/forecast/NYZ072",
+      "type": "Feature",
+      "geometry": null,
+      "properties": {
+        "@id": "https://api.weather.gov/zones/forecast/NYZ072",
+        "@type": "wx:Zone",
+        "id": "NYZ072",
+        "type": "public",
+        "name": "New York (Manhattan)",
+        "effectiveDate": "2021-07-20T00:00:00+00:00",
+        "expirationDate": "2200-01-01T00:00:00+00:00",
+        "state": "NY",
+        "cwa": ["OKX"],
+        "forecastOffices": ["https://api.weather.gov/offices/OKX"],
+        "timeZone": ["America/New_York"],
+        "observationStations": ["https://api.weather.gov/stations/KNYC"],
+        "radarStation": null
+      }
+    }"#,
+  )
+  .unwrap();
+  let zone = res.properties;
+  assert_eq!(zone.id, "NYZ072");
+  assert_eq!(zone.name, "New York (Manhattan)");
+  assert_eq!(zone.state.as_deref(), Some("NY"));
+  assert_eq!(zone.zone_type, "public");
+}
+
+/// Result of GET /alerts
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlertCollection {
+  pub features: Vec<Alert>,
+}
+
+/// Single feature of an AlertCollection
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Alert {
+  pub id: String,
+  #[serde(default)]
+  pub geometry: Option<AlertGeometry>,
+  pub properties: AlertProps,
+}
+
+/// GeoJSON geometry of the area affected by an Alert. Note that
+/// coordinates are in (lng, lat) order, and are validated when
+/// deserializing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AlertGeometry {
+  Polygon {
+    coordinates: Vec<Vec<(Longitude, Latitude)>>,
+  },
+  MultiPolygon {
+    coordinates: Vec<Vec<Vec<(Longitude, Latitude)>>>,
+  },
+}
+
+impl Alert {
+  /// Whether the alert is in effect at a given time, from `onset` (or
+  /// `effective` if there is none) until `ends`, or `expires` if the
+  /// alert has no end time
+  pub fn is_active(&self, at: DateTime<Local>) -> bool {
+    let p = &self.properties;
+    p.onset.unwrap_or(p.effective) <= at && at < p.ends.unwrap_or(p.expires)
+  }
+
+  /// Outer ring of the affected area, or the first polygon's outer
+  /// ring for a MultiPolygon. None if the alert has no geometry.
+  pub fn polygon(&self) -> Option<Vec<Point>> {
+    let ring = match self.geometry.as_ref()? {
+      AlertGeometry::Polygon { coordinates } => coordinates.first()?,
+      AlertGeometry::MultiPolygon { coordinates } => coordinates.first()?.first()?,
+    };
+    Some(
+      ring
+        .iter()
+        .map(|&(lng, lat)| Point::from_coordinates(lat, lng))
+        .collect(),
+    )
+  }
+}
+
+#[cfg(test)]
+fn test_alert(ends: Option<&str>) -> Alert {
+  serde_json::from_value(serde_json::json!({
+    "id": "urn:oid:0",
+    "geometry": {
+      "type": "Polygon",
+      "coordinates": [[[-74.02, 40.70], [-73.97, 40.70], [-73.97, 40.75], [-74.02, 40.70]]]
+    },
+    "properties": {
+      "event": "Heat Advisory",
+      "headline": null,
+      "severity": "Moderate",
+      "urgency": "Expected",
+      "certainty": "Likely",
+      "effective": "2021-08-21T12:00:00-04:00",
+      "onset": "2021-08-21T12:00:00-04:00",
+      "ends": ends,
+      "expires": "2021-08-21T20:00:00-04:00",
+      "areaDesc": "New York (Manhattan)",
+      "description": "Heat index values up to 100.",
+      "instruction": null
+    }
+  }))
+  .unwrap()
+}
+
+#[test]
+fn alert_is_active() {
+  let at = |s: &str| {
+    DateTime::parse_from_rfc3339(s)
+      .unwrap()
+      .with_timezone(&Local)
+  };
+  let alert = test_alert(None);
+  assert!(!alert.is_active(at("2021-08-21T11:00:00-04:00")));
+  assert!(alert.is_active(at("2021-08-21T15:00:00-04:00")));
+  assert!(!alert.is_active(at("2021-08-21T21:00:00-04:00")));
+  let alert = test_alert(Some("2021-08-21T14:00:00-04:00"));
+  assert!(!alert.is_active(at("2021-08-21T15:00:00-04:00")));
+  // issued at noon for a hazard starting at 4pm
+  let mut alert = test_alert(None);
+  alert.properties.onset = Some(at("2021-08-21T16:00:00-04:00"));
+  assert!(!alert.is_active(at("2021-08-21T15:00:00-04:00")));
+  assert!(alert.is_active(at("2021-08-21T17:00:00-04:00")));
+  alert.properties.onset = None;
+  assert!(alert.is_active(at("2021-08-21T15:00:00-04:00")));
+}
+
+#[test]
+fn alert_polygon() {
+  let mut alert = test_alert(None);
+  let ring = alert.polygon().unwrap();
+  assert_eq!(ring.len(), 4);
+  assert_eq!(ring[0], Point::new(40.70, -74.02));
+  assert_eq!(ring[2], Point::new(40.75, -73.97));
+  alert.geometry = Some(
+    serde_json::from_str(
+      r#"{"type": "MultiPolygon", "coordinates": [[[[-74.0, 40.0], [-73.0, 41.0], [-74.0, 40.0]]]]}"#,
+    )
+    .unwrap(),
+  );
+  assert_eq!(alert.polygon().unwrap()[1], Point::new(41.0, -73.0));
+  alert.geometry = None;
+  assert_eq!(alert.polygon(), None);
+  let alert: Alert = serde_json::from_str(
+    r#"{"id": "urn:oid:1", "geometry": null, "properties": {
+      "event": "Flood Warning", "headline": null, "severity": "Minor",
+      "urgency": "Expected", "certainty": "Likely",
+      "effective": "2021-08-21T18:00:00-04:00",
+      "expires": "2021-08-22T06:00:00-04:00",
+      "areaDesc": "Kings", "description": "", "instruction": null
+    }}"#,
+  )
+  .unwrap();
+  assert!(alert.geometry.is_none());
+  // the second vertex has an out-of-range latitude
+  assert!(serde_json::from_str::<AlertGeometry>(
+    r#"{"type": "Polygon", "coordinates": [[[-74.02, 40.70], [-73.97, 140.70], [-73.97, 40.75]]]}"#
+  )
+  .is_err());
+  let geometry = test_alert(None).geometry.unwrap();
+  let json = serde_json::to_string(&geometry).unwrap();
+  assert_eq!(
+    serde_json::from_str::<AlertGeometry>(&json).unwrap(),
+    geometry
+  );
+}
+
+/// Distance in degrees (about a meter) within which point_in_polygon
+/// considers a Point to be on an edge
+const EDGE_TOLERANCE: f32 = 1e-5;
+
+/// Whether a Point falls inside a polygon ring (e.g. from
+/// Alert::polygon), using ray casting. Points on an edge or corner
+/// of the ring, within EDGE_TOLERANCE, count as inside.
+pub fn point_in_polygon(point: &Point, ring: &[Point]) -> bool {
+  if ring.len() < 3 {
+    return false;
+  }
+  let (mut min, mut max) = (Point::new(90.0, 180.0), Point::new(-90.0, -180.0));
+  for p in ring {
+    min = Point::new(min.lat.min(p.lat), min.lng.min(p.lng));
+    max = Point::new(max.lat.max(p.lat), max.lng.max(p.lng));
+  }
+  if point.lat < min.lat || point.lat > max.lat || point.lng < min.lng || point.lng > max.lng {
+    return false;
+  }
+  let (x, y) = (point.lng, point.lat);
+  let mut inside = false;
+  let mut j = ring.len() - 1;
+  for i in 0..ring.len() {
+    let (xi, yi, xj, yj) = (ring[i].lng, ring[i].lat, ring[j].lng, ring[j].lat);
+    // on the segment between ring[j] and ring[i], where cross / len
+    // is the distance from the line through them
+    let cross = (x - xi) * (yj - yi) - (y - yi) * (xj - xi);
+    let len = (xj - xi).hypot(yj - yi);
+    if cross.abs() <= EDGE_TOLERANCE * len
+      && x >= xi.min(xj) - EDGE_TOLERANCE
+      && x <= xi.max(xj) + EDGE_TOLERANCE
+      && y >= yi.min(yj) - EDGE_TOLERANCE
+      && y <= yi.max(yj) + EDGE_TOLERANCE
+    {
+      return true;
+    }
+    if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+      inside = !inside;
+    }
+    j = i;
+  }
+  inside
+}
+
+#[test]
+fn point_in_square() {
+  let ring = [
+    Point::new(0.0, 0.0),
+    Point::new(0.0, 1.0),
+    Point::new(1.0, 1.0),
+    Point::new(1.0, 0.0),
+    Point::new(0.0, 0.0),
+  ];
+  assert!(point_in_polygon(&Point::new(0.5, 0.5), &ring));
+  assert!(!point_in_polygon(&Point::new(1.5, 0.5), &ring));
+  assert!(!point_in_polygon(&Point::new(-0.5, -0.5), &ring));
+  assert!(point_in_polygon(&Point::new(0.0, 0.0), &ring));
+  assert!(point_in_polygon(&Point::new(1.0, 1.0), &ring));
+  assert!(point_in_polygon(&Point::new(0.5, 1.0), &ring));
+  assert!(!point_in_polygon(&Point::new(0.5, 0.5), &ring[..2]));
+}
+
+#[test]
+fn point_on_diagonal_edge() {
+  let ring = [
+    Point::new(40.70, -74.02),
+    Point::new(40.75, -74.02),
+    Point::new(40.70, -73.97),
+  ];
+  // 37% of the way along the diagonal edge, which rounding puts just
+  // outside it
+  let edge = Point::new(40.75 - 0.05 * 0.37, -74.02 + 0.05 * 0.37);
+  assert!(point_in_polygon(&edge, &ring));
+  assert!(!point_in_polygon(
+    &Point::new(edge.lat + 1e-3, edge.lng),
+    &ring
+  ));
+}
+
+/// Inner properties object of Alert
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlertProps {
+  pub event: String,
+  pub headline: Option<String>,
+  pub severity: String,
+  pub urgency: String,
+  pub certainty: String,
+  pub effective: DateTime<Local>,
+  pub onset: Option<DateTime<Local>>,
+  pub ends: Option<DateTime<Local>>,
+  pub expires: DateTime<Local>,
+  #[serde(rename(deserialize = "areaDesc"))]
+  pub area_desc: String,
+  pub description: String,
+  pub instruction: Option<String>,
+}
+
+/// Severity of an alert, matching AlertProps.severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+  Extreme,
+  Severe,
+  Moderate,
+  Minor,
+  Unknown,
+}
+
+impl Severity {
+  /// Value of the `severity` query parameter
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Severity::Extreme => "Extreme",
+      Severity::Severe => "Severe",
+      Severity::Moderate => "Moderate",
+      Severity::Minor => "Minor",
+      Severity::Unknown => "Unknown",
+    }
+  }
+}
+
+/// Urgency of an alert, matching AlertProps.urgency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+  Immediate,
+  Expected,
+  Future,
+  Past,
+  Unknown,
+}
+
+impl Urgency {
+  /// Value of the `urgency` query parameter
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Urgency::Immediate => "Immediate",
+      Urgency::Expected => "Expected",
+      Urgency::Future => "Future",
+      Urgency::Past => "Past",
+      Urgency::Unknown => "Unknown",
+    }
+  }
+}
+
+/// Optional filters of an alerts query. An empty filter matches every
+/// alert.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AlertFilter {
+  pub severity: Vec<Severity>,
+  pub urgency: Vec<Urgency>,
+  /// Event names, e.g. "Tornado Warning"
+  pub event: Vec<String>,
+}
+
+impl AlertFilter {
+  /// Create an empty AlertFilter
+  pub fn new() -> Self {
+    AlertFilter::default()
+  }
+
+  /// Also match alerts of the given severity
+  pub fn severity(mut self, severity: Severity) -> Self {
+    self.severity.push(severity);
+    self
+  }
+
+  /// Also match alerts of the given urgency
+  pub fn urgency(mut self, urgency: Urgency) -> Self {
+    self.urgency.push(urgency);
+    self
+  }
+
+  /// Also match alerts of the given event name, e.g. "Tornado Warning"
+  pub fn event(mut self, event: impl Into<String>) -> Self {
+    self.event.push(event.into());
+    self
+  }
+
+  /// Query string parameters of the filter, with multiple values
+  /// comma-separated, e.g. [("severity", "Extreme,Severe"),
+  /// ("event", "Tornado Warning")]
+  fn query(&self) -> Vec<(&'static str, String)> {
+    let mut query = Vec::new();
+    if !self.severity.is_empty() {
+      let v: Vec<&str> = self.severity.iter().map(Severity::as_str).collect();
+      query.push(("severity", v.join(",")));
+    }
+    if !self.urgency.is_empty() {
+      let v: Vec<&str> = self.urgency.iter().map(Urgency::as_str).collect();
+      query.push(("urgency", v.join(",")));
+    }
+    if !self.event.is_empty() {
+      query.push(("event", self.event.join(",")));
+    }
+    query
   }
+
+  /// Whether an alert matches the filter. Severity, urgency and event
+  /// names are all compared case-insensitively.
+  pub fn matches(&self, alert: &Alert) -> bool {
+    let p = &alert.properties;
+    (self.severity.is_empty()
+      || self
+        .severity
+        .iter()
+        .any(|s| s.as_str().eq_ignore_ascii_case(&p.severity)))
+      && (self.urgency.is_empty()
+        || self
+          .urgency
+          .iter()
+          .any(|u| u.as_str().eq_ignore_ascii_case(&p.urgency)))
+      && (self.event.is_empty() || self.event.iter().any(|e| e.eq_ignore_ascii_case(&p.event)))
+  }
+}
+
+#[test]
+fn alert_filter_matches_case_insensitively() {
+  let mut alert = test_alert(None);
+  alert.properties.severity = "MODERATE".to_string();
+  alert.properties.urgency = "expected".to_string();
+  alert.properties.event = "heat advisory".to_string();
+  let filter = AlertFilter::new()
+    .severity(Severity::Moderate)
+    .urgency(Urgency::Expected)
+    .event("Heat Advisory");
+  assert!(filter.matches(&alert));
+  assert!(!AlertFilter::new().severity(Severity::Minor).matches(&alert));
+}
+
+#[test]
+fn alert_filter_query() {
+  assert!(AlertFilter::new().query().is_empty());
+  let filter = AlertFilter::new()
+    .severity(Severity::Extreme)
+    .severity(Severity::Severe)
+    .urgency(Urgency::Immediate);
+  assert_eq!(
+    filter.query(),
+    [
+      ("severity", "Extreme,Severe".to_string()),
+      ("urgency", "Immediate".to_string())
+    ]
+  );
+  let filter = AlertFilter::new()
+    .event("Tornado Warning")
+    .event("Severe Thunderstorm Warning");
+  assert_eq!(
+    filter.query(),
+    [(
+      "event",
+      "Tornado Warning,Severe Thunderstorm Warning".to_string()
+    )]
+  );
+}
+
+/// Get the active alerts for a state, given its two-letter code
+/// (e.g. "NY").
+pub async fn get_alerts(state: &str, client: &Client) -> Result<Vec<Alert>, Error> {
+  WeatherClient::from(client.clone()).alerts(state).await
+}
+
+/// Get the active alerts affecting a Point
+pub async fn get_alerts_for_point(pnt: &Point, client: &Client) -> Result<Vec<Alert>, Error> {
+  WeatherClient::from(client.clone())
+    .alerts_for_point(pnt)
+    .await
+}
+
+/// Get the active alerts for a zone, given its ID (e.g. "NYZ072")
+pub async fn get_alerts_for_zone(zone_id: &str, client: &Client) -> Result<Vec<Alert>, Error> {
+  WeatherClient::from(client.clone())
+    .alerts_for_zone(zone_id)
+    .await
+}
+
+/// Get the active alerts for a state matching an AlertFilter
+pub async fn get_alerts_filtered(
+  state: &str,
+  filter: &AlertFilter,
+  client: &Client,
+) -> Result<Vec<Alert>, Error> {
+  WeatherClient::from(client.clone())
+    .alerts_filtered(state, filter)
+    .await
+}
+
+/// Fetch the forecast of a City from a WeatherSource (e.g. a
+/// WeatherClient or FixtureSource) and bundle them together.
+///
+/// Returns Error::PointNotCovered if NWS has no data for the city's
+/// coordinates (e.g. they resolve to a point in the ocean).
+pub async fn fetch_weather_bundle<S: WeatherSource>(
+  city: City,
+  source: &S,
+) -> Result<WeatherBundle, Error> {
+  let pnt = Point::from(&city);
+  let info = match source.point(&pnt).await {
+    Err(Error::Api { status: 404, .. }) => {
+      return Err(Error::PointNotCovered {
+        lat: pnt.lat,
+        lng: pnt.lng,
+      })
+    }
+    res => res?,
+  };
+  let forecast = source.forecast(&info).await?;
+  Ok(WeatherBundle::new(city, forecast))
+}
+
+/// Poll the forecast of a point every `interval`, yielding each
+/// result. Errors are yielded as items without ending the stream.
+pub fn forecast_stream(
+  point: Point,
+  interval: std::time::Duration,
+  client: Client,
+) -> impl futures::Stream<Item = Result<Forecast, Error>> {
+  WeatherClient::from(client).forecast_stream(point, interval)
+}
+
+/// Number of points fetch_many looks up at once
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Fetch the forecasts of many points concurrently, returning a result
+/// per point in the same order as `points`.
+///
+/// At most DEFAULT_CONCURRENCY points are looked up at once. Use
+/// WeatherClient::forecasts to choose a different limit.
+pub async fn fetch_many(points: &[Point], client: &Client) -> Vec<Result<Forecast, Error>> {
+  WeatherClient::from(client.clone())
+    .forecasts(points, DEFAULT_CONCURRENCY)
+    .await
+}
+
+/// Fetch the first 10 periods of the hourly forecast at a
+/// coordinate
+pub async fn fetch_report(
+  lat: f32,
+  lng: f32,
+  client: &Client,
+) -> Result<Vec<ForecastBundle>, Error> {
+  WeatherClient::from(client.clone())
+    .report(&Point { lat, lng })
+    .await
+}
+
+/// Print the first 10 periods of the hourly forecast at a coordinate,
+/// using a WeatherClient with the DEFAULT_USER_AGENT. Use fetch_report
+/// with a Client identifying the application instead where possible.
+pub async fn weather_report(lat: f32, lng: f32) -> Result<(), Error> {
+  let client = WeatherClient::builder().build()?;
+  let report = client.report(&Point { lat, lng }).await?;
+  print_report(&report);
   Ok(())
 }
+
+/// The first 10 periods of a forecast, as returned by fetch_report
+fn report_bundles(forecast: &Forecast) -> Vec<ForecastBundle> {
+  forecast
+    .properties
+    .periods
+    .iter()
+    .take(10)
+    .map(ForecastBundle::from)
+    .collect()
+}
+
+/// Print a report built by fetch_report
+fn print_report(report: &[ForecastBundle]) {
+  for i in report {
+    println!("{}", i);
+  }
+}
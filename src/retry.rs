@@ -0,0 +1,93 @@
+//! Retry with exponential backoff
+use crate::Error;
+use chrono::{DateTime, Utc};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Call `f` up to `attempts` times, retrying transient failures (see
+/// Error::is_transient) with exponential backoff and jitter.
+///
+/// The delay before retry n is `base_delay * 2^(n-1)`, plus up to half
+/// that again as jitter, or the Retry-After delay of an
+/// Error::RateLimited if that is longer. Other errors are returned
+/// immediately.
+pub async fn retry<F, Fut, T>(attempts: u32, base_delay: Duration, mut f: F) -> Result<T, Error>
+where
+  F: FnMut() -> Fut,
+  Fut: Future<Output = Result<T, Error>>,
+{
+  let mut attempt = 1;
+  loop {
+    match f().await {
+      Err(e) if attempt < attempts && e.is_transient() => {
+        let mut delay = backoff(base_delay, attempt);
+        if let Error::RateLimited { retry_after } = e {
+          delay = delay.max(retry_after);
+        }
+        log::debug!("attempt {} failed, retrying in {:?}: {}", attempt, delay, e);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+      }
+      res => return res,
+    }
+  }
+}
+
+/// Backoff delay before retrying after `attempt` failed attempts
+fn backoff(base_delay: Duration, attempt: u32) -> Duration {
+  let delay = base_delay.saturating_mul(2u32.saturating_pow(attempt - 1));
+  let jitter = RandomState::new().build_hasher().finish() % 1000;
+  delay + delay.mul_f64(jitter as f64 / 2000.0)
+}
+
+/// Parse a Retry-After header value, in either the delay-seconds or
+/// HTTP-date form, into the Duration to wait from `now`.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+  let value = value.trim();
+  if let Ok(secs) = value.parse::<u64>() {
+    return Some(Duration::from_secs(secs));
+  }
+  let date = DateTime::parse_from_rfc2822(value).ok()?;
+  Some(
+    (date.with_timezone(&Utc) - now)
+      .to_std()
+      .unwrap_or(Duration::ZERO),
+  )
+}
+
+#[test]
+fn retry_after_seconds() {
+  assert_eq!(
+    parse_retry_after("120", Utc::now()),
+    Some(Duration::from_secs(120))
+  );
+  assert_eq!(parse_retry_after("soon", Utc::now()), None);
+}
+
+#[test]
+fn retry_after_http_date() {
+  let now = DateTime::parse_from_rfc3339("2015-10-21T07:27:30Z")
+    .unwrap()
+    .with_timezone(&Utc);
+  assert_eq!(
+    parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+    Some(Duration::from_secs(30))
+  );
+  // dates in the past mean retry immediately
+  assert_eq!(
+    parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+    Some(Duration::ZERO)
+  );
+}
+
+#[test]
+fn backoff_doubles() {
+  let base = Duration::from_millis(100);
+  for (attempt, min) in [(1, 100), (2, 200), (3, 400)] {
+    let delay = backoff(base, attempt);
+    assert!(delay >= Duration::from_millis(min));
+    assert!(delay <= Duration::from_millis(min * 3 / 2));
+  }
+}
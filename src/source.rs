@@ -0,0 +1,351 @@
+//! Abstraction over where weather data comes from
+//!
+//! Code that is generic over WeatherSource can be driven by a
+//! WeatherClient in production and a FixtureSource in tests.
+use crate::client::{check_state, observations_path};
+use crate::{
+  Alert, AlertCollection, Error, Feature, Forecast, GridData, Observation, Office, Page, Point,
+  PointInfo, Station, StationCollection, WeatherClient, Zone,
+};
+use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use std::future::{ready, Future};
+use std::path::PathBuf;
+
+/// Source of the data returned by the NWS API getters
+pub trait WeatherSource {
+  /// Metadata of a point, as WeatherClient::point
+  fn point(&self, pnt: &Point) -> impl Future<Output = Result<PointInfo, Error>> + Send;
+  /// Forecast of a point, as WeatherClient::forecast
+  fn forecast(&self, pnt: &PointInfo) -> impl Future<Output = Result<Forecast, Error>> + Send;
+  /// Hourly forecast of a point, as WeatherClient::forecast_hourly
+  fn forecast_hourly(
+    &self,
+    pnt: &PointInfo,
+  ) -> impl Future<Output = Result<Forecast, Error>> + Send;
+  /// Raw gridpoint forecast of a point, as WeatherClient::grid_data
+  fn grid_data(&self, pnt: &PointInfo) -> impl Future<Output = Result<GridData, Error>> + Send;
+  /// Observation stations near a point, as
+  /// WeatherClient::observation_stations
+  fn observation_stations(
+    &self,
+    pnt: &PointInfo,
+  ) -> impl Future<Output = Result<Vec<Station>, Error>> + Send;
+  /// Observations of a station between two times, as
+  /// WeatherClient::observations
+  fn observations(
+    &self,
+    station_id: &str,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+  ) -> impl Future<Output = Result<Vec<Observation>, Error>> + Send;
+  /// Latest observation of a station, as
+  /// WeatherClient::latest_observation
+  fn latest_observation(
+    &self,
+    station_id: &str,
+  ) -> impl Future<Output = Result<Observation, Error>> + Send;
+  /// Metadata of a zone given its URL, as WeatherClient::zone
+  fn zone(&self, url: &str) -> impl Future<Output = Result<Zone, Error>> + Send;
+  /// Forecast office of a point, as WeatherClient::office
+  fn office(&self, pnt: &PointInfo) -> impl Future<Output = Result<Office, Error>> + Send;
+  /// Active alerts for a state, as WeatherClient::alerts
+  fn alerts(&self, state: &str) -> impl Future<Output = Result<Vec<Alert>, Error>> + Send;
+  /// Active alerts affecting a point, as
+  /// WeatherClient::alerts_for_point
+  fn alerts_for_point(&self, pnt: &Point)
+    -> impl Future<Output = Result<Vec<Alert>, Error>> + Send;
+  /// Active alerts for a zone, as WeatherClient::alerts_for_zone
+  fn alerts_for_zone(
+    &self,
+    zone_id: &str,
+  ) -> impl Future<Output = Result<Vec<Alert>, Error>> + Send;
+}
+
+impl WeatherSource for WeatherClient {
+  fn point(&self, pnt: &Point) -> impl Future<Output = Result<PointInfo, Error>> + Send {
+    WeatherClient::point(self, pnt)
+  }
+  fn forecast(&self, pnt: &PointInfo) -> impl Future<Output = Result<Forecast, Error>> + Send {
+    WeatherClient::forecast(self, pnt)
+  }
+  fn forecast_hourly(
+    &self,
+    pnt: &PointInfo,
+  ) -> impl Future<Output = Result<Forecast, Error>> + Send {
+    WeatherClient::forecast_hourly(self, pnt)
+  }
+  fn grid_data(&self, pnt: &PointInfo) -> impl Future<Output = Result<GridData, Error>> + Send {
+    WeatherClient::grid_data(self, pnt)
+  }
+  fn observation_stations(
+    &self,
+    pnt: &PointInfo,
+  ) -> impl Future<Output = Result<Vec<Station>, Error>> + Send {
+    WeatherClient::observation_stations(self, pnt)
+  }
+  fn observations(
+    &self,
+    station_id: &str,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+  ) -> impl Future<Output = Result<Vec<Observation>, Error>> + Send {
+    WeatherClient::observations(self, station_id, start, end)
+  }
+  fn latest_observation(
+    &self,
+    station_id: &str,
+  ) -> impl Future<Output = Result<Observation, Error>> + Send {
+    WeatherClient::latest_observation(self, station_id)
+  }
+  fn zone(&self, url: &str) -> impl Future<Output = Result<Zone, Error>> + Send {
+    WeatherClient::zone(self, url)
+  }
+  fn office(&self, pnt: &PointInfo) -> impl Future<Output = Result<Office, Error>> + Send {
+    WeatherClient::office(self, pnt)
+  }
+  fn alerts(&self, state: &str) -> impl Future<Output = Result<Vec<Alert>, Error>> + Send {
+    WeatherClient::alerts(self, state)
+  }
+  fn alerts_for_point(
+    &self,
+    pnt: &Point,
+  ) -> impl Future<Output = Result<Vec<Alert>, Error>> + Send {
+    WeatherClient::alerts_for_point(self, pnt)
+  }
+  fn alerts_for_zone(
+    &self,
+    zone_id: &str,
+  ) -> impl Future<Output = Result<Vec<Alert>, Error>> + Send {
+    WeatherClient::alerts_for_zone(self, zone_id)
+  }
+}
+
+/// WeatherSource serving canned JSON responses from a directory.
+///
+/// Each request is read from `{dir}/{path}.json`, where `path` is the
+/// request URL relative to the API root (e.g.
+/// `points/40.7128,-74.006.json`, or
+/// `gridpoints/OKX/33,35/forecast.json` for the forecast URL of a
+/// PointInfo). A query string is appended with `?`, `=`, `&` and `:`
+/// replaced by `_`, so active alerts for NY are read from
+/// `alerts/active_area_NY.json`. Paginated listings are read from a
+/// single file. A missing file is an Error::Api with status 404.
+#[derive(Debug, Clone)]
+pub struct FixtureSource {
+  dir: PathBuf,
+}
+
+impl FixtureSource {
+  /// Create a new FixtureSource reading from `dir`
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    FixtureSource { dir: dir.into() }
+  }
+
+  /// Deserialize the fixture of a path or absolute API URL
+  fn load<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+    let path = url
+      .find("://")
+      .and_then(|i| url[i + 3..].split_once('/'))
+      .map_or(url, |(_, path)| path);
+    let path = match path.trim_start_matches('/').split_once('?') {
+      Some((path, query)) => format!("{}_{}", path, query.replace(['=', '&', ':'], "_")),
+      None => path.trim_start_matches('/').to_string(),
+    };
+    let file = self.dir.join(format!("{}.json", path));
+    match std::fs::read_to_string(&file) {
+      Ok(body) => Ok(serde_json::from_str(&body)?),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(Error::Api {
+        status: 404,
+        body: format!("no fixture at {}", file.display()),
+        problem: None,
+      }),
+      Err(e) => Err(e.into()),
+    }
+  }
+}
+
+impl WeatherSource for FixtureSource {
+  fn point(&self, pnt: &Point) -> impl Future<Output = Result<PointInfo, Error>> + Send {
+    ready(
+      pnt
+        .nws_coordinates()
+        .and_then(|c| self.load(&format!("points/{}", c))),
+    )
+  }
+  fn forecast(&self, pnt: &PointInfo) -> impl Future<Output = Result<Forecast, Error>> + Send {
+    ready(self.load(&pnt.properties.forecast))
+  }
+  fn forecast_hourly(
+    &self,
+    pnt: &PointInfo,
+  ) -> impl Future<Output = Result<Forecast, Error>> + Send {
+    ready(self.load(&pnt.properties.forecast_hourly))
+  }
+  fn grid_data(&self, pnt: &PointInfo) -> impl Future<Output = Result<GridData, Error>> + Send {
+    ready(self.load(&pnt.properties.forecast_grid_data))
+  }
+  fn observation_stations(
+    &self,
+    pnt: &PointInfo,
+  ) -> impl Future<Output = Result<Vec<Station>, Error>> + Send {
+    ready(
+      self
+        .load::<StationCollection>(&pnt.properties.observation_stations)
+        .map(|c| c.features),
+    )
+  }
+  fn observations(
+    &self,
+    station_id: &str,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+  ) -> impl Future<Output = Result<Vec<Observation>, Error>> + Send {
+    ready(
+      self
+        .load::<Page<Feature<Observation>>>(&observations_path(station_id, start, end))
+        .map(|p| p.features.into_iter().map(|f| f.properties).collect()),
+    )
+  }
+  fn latest_observation(
+    &self,
+    station_id: &str,
+  ) -> impl Future<Output = Result<Observation, Error>> + Send {
+    ready(
+      self
+        .load::<Feature<Observation>>(&format!("stations/{}/observations/latest", station_id))
+        .map(|f| f.properties),
+    )
+  }
+  fn zone(&self, url: &str) -> impl Future<Output = Result<Zone, Error>> + Send {
+    ready(self.load::<Feature<Zone>>(url).map(|f| f.properties))
+  }
+  fn office(&self, pnt: &PointInfo) -> impl Future<Output = Result<Office, Error>> + Send {
+    ready(self.load(&pnt.properties.forecast_office))
+  }
+  fn alerts(&self, state: &str) -> impl Future<Output = Result<Vec<Alert>, Error>> + Send {
+    ready(check_state(state).and_then(|()| {
+      self
+        .load::<AlertCollection>(&format!(
+          "alerts/active?area={}",
+          state.to_ascii_uppercase()
+        ))
+        .map(|a| a.features)
+    }))
+  }
+  fn alerts_for_point(
+    &self,
+    pnt: &Point,
+  ) -> impl Future<Output = Result<Vec<Alert>, Error>> + Send {
+    ready(pnt.nws_coordinates().and_then(|c| {
+      self
+        .load::<AlertCollection>(&format!("alerts/active?point={}", c))
+        .map(|a| a.features)
+    }))
+  }
+  fn alerts_for_zone(
+    &self,
+    zone_id: &str,
+  ) -> impl Future<Output = Result<Vec<Alert>, Error>> + Send {
+    ready(
+      self
+        .load::<AlertCollection>(&format!("alerts/active/zone/{}", zone_id))
+        .map(|a| a.features),
+    )
+  }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn fixture_weather_bundle() {
+  use crate::City;
+
+  let dir = tempfile::tempdir().unwrap();
+  std::fs::create_dir_all(dir.path().join("points")).unwrap();
+  std::fs::create_dir_all(dir.path().join("gridpoints/OKX/33,35")).unwrap();
+  std::fs::write(
+    dir.path().join("points/40.7128,-74.006.json"),
+    crate::TEST_POINT,
+  )
+  .unwrap();
+  std::fs::write(
+    dir.path().join("gridpoints/OKX/33,35/forecast.json"),
+    crate::test_forecast_json(2),
+  )
+  .unwrap();
+  let source = FixtureSource::new(dir.path());
+  let city = City {
+    city: "New York".to_string(),
+    state_id: "NY".to_string(),
+    lat: 40.7128,
+    lng: -74.006,
+  };
+  let bundle = crate::fetch_weather_bundle(city, &source).await.unwrap();
+  assert_eq!(bundle.forecast.len(), 2);
+  assert_eq!(bundle.forecast[0].temperature, 72);
+  let ocean = City {
+    city: "Atlantic".to_string(),
+    state_id: "NY".to_string(),
+    lat: 39.0,
+    lng: -70.0,
+  };
+  assert!(matches!(
+    crate::fetch_weather_bundle(ocean, &source).await,
+    Err(Error::PointNotCovered { .. })
+  ));
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn fixture_alerts() {
+  let dir = tempfile::tempdir().unwrap();
+  std::fs::create_dir_all(dir.path().join("alerts")).unwrap();
+  std::fs::write(
+    dir.path().join("alerts/active_area_NY.json"),
+    r#"{"features": []}"#,
+  )
+  .unwrap();
+  let source = FixtureSource::new(dir.path());
+  assert!(WeatherSource::alerts(&source, "ny")
+    .await
+    .unwrap()
+    .is_empty());
+  assert!(matches!(
+    WeatherSource::alerts(&source, "NJ").await,
+    Err(Error::Api { status: 404, .. })
+  ));
+  // validated like WeatherClient::alerts before looking for a fixture
+  assert!(matches!(
+    WeatherSource::alerts(&source, "../NY").await,
+    Err(Error::InvalidState(_))
+  ));
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn fixture_observations() {
+  let dir = tempfile::tempdir().unwrap();
+  std::fs::create_dir_all(dir.path().join("stations/KNYC")).unwrap();
+  std::fs::write(
+    dir
+      .path()
+      .join("stations/KNYC/observations_start_2021-08-21T00_00_00Z_end_2021-08-22T00_00_00Z.json"),
+    r#"{"features": [{"properties": {"timestamp": "2021-08-21T20:51:00+00:00"}}]}"#,
+  )
+  .unwrap();
+  let source = FixtureSource::new(dir.path());
+  let at = |s: &str| {
+    DateTime::parse_from_rfc3339(s)
+      .unwrap()
+      .with_timezone(&Local)
+  };
+  let obs = WeatherSource::observations(
+    &source,
+    "KNYC",
+    at("2021-08-21T00:00:00+00:00"),
+    at("2021-08-22T00:00:00+00:00"),
+  )
+  .await
+  .unwrap();
+  assert_eq!(obs.len(), 1);
+}
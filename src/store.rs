@@ -0,0 +1,53 @@
+//! Embedded WeatherBundle store
+use crate::{Error, WeatherBundle};
+use std::path::Path;
+
+/// sled database of WeatherBundles keyed by "{state}:{city}"
+pub struct WeatherStore {
+  db: sled::Db,
+}
+
+impl WeatherStore {
+  /// Open or create a WeatherStore at `path`
+  pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+    Ok(WeatherStore {
+      db: sled::open(path)?,
+    })
+  }
+
+  /// Insert a WeatherBundle, replacing any previous bundle of the same
+  /// City
+  pub fn put(&self, bundle: &WeatherBundle) -> Result<(), Error> {
+    let key = key(&bundle.location.city, &bundle.location.state_id);
+    self.db.insert(key, serde_json::to_vec(bundle)?)?;
+    Ok(())
+  }
+
+  /// Get the WeatherBundle of a City, given its name and state
+  pub fn get(&self, city: &str, state: &str) -> Result<Option<WeatherBundle>, Error> {
+    match self.db.get(key(city, state))? {
+      Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+      None => Ok(None),
+    }
+  }
+}
+
+fn key(city: &str, state: &str) -> String {
+  format!("{}:{}", state, city)
+}
+
+#[test]
+fn put_get() {
+  let dir = tempfile::tempdir().unwrap();
+  let store = WeatherStore::open(dir.path()).unwrap();
+  let city = crate::City {
+    city: "New York".to_string(),
+    state_id: "NY".to_string(),
+    lat: 40.6943,
+    lng: -73.9249,
+  };
+  let bundle = WeatherBundle::new(city, crate::test_hourly_forecast(2));
+  store.put(&bundle).unwrap();
+  assert_eq!(store.get("New York", "NY").unwrap(), Some(bundle));
+  assert_eq!(store.get("Chicago", "IL").unwrap(), None);
+}
@@ -0,0 +1,36 @@
+//! Unit conversions
+/// Kilometers in a statute mile
+pub const KM_PER_MILE: f64 = 1.609344;
+
+/// Kilometers in a nautical mile
+pub const KM_PER_NAUTICAL_MILE: f64 = 1.852;
+
+/// Meters in an international foot
+pub const METERS_PER_FOOT: f64 = 0.3048;
+
+/// Convert km/h to mph
+pub fn kmh_to_mph(kmh: f64) -> f64 {
+  kmh / KM_PER_MILE
+}
+
+/// Convert mph to km/h
+pub fn mph_to_kmh(mph: f64) -> f64 {
+  mph * KM_PER_MILE
+}
+
+/// Convert m/s to km/h
+pub fn ms_to_kmh(ms: f64) -> f64 {
+  ms * 3.6
+}
+
+/// Convert knots to km/h
+pub fn knots_to_kmh(knots: f64) -> f64 {
+  knots * KM_PER_NAUTICAL_MILE
+}
+
+#[test]
+fn kmh_mph() {
+  assert!((kmh_to_mph(100.0) - 62.137_119).abs() < 1e-6);
+  assert!((mph_to_kmh(60.0) - 96.56064).abs() < 1e-9);
+  assert!((kmh_to_mph(mph_to_kmh(25.0)) - 25.0).abs() < 1e-9);
+}